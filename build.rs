@@ -0,0 +1,18 @@
+use std::fs;
+
+// Exposes the resolved mqtt-endpoint-tokio version to the About window.
+fn main() {
+    println!("cargo:rerun-if-changed=Cargo.lock");
+    let lock = fs::read_to_string("Cargo.lock").unwrap_or_default();
+    let version = lock
+        .split("[[package]]")
+        .find(|package| package.contains("\nname = \"mqtt-endpoint-tokio\"\n"))
+        .and_then(|package| {
+            package
+                .lines()
+                .find_map(|line| line.strip_prefix("version = "))
+        })
+        .map(|version| version.trim_matches('"'))
+        .unwrap_or("unknown");
+    println!("cargo:rustc-env=MQTT_ENDPOINT_VERSION={version}");
+}
@@ -4,7 +4,10 @@ use std::path::{Path, PathBuf};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
 
-use crate::models::mqtt::{ConnectionInputMode, MqttLoginData, TlsVerificationMode, TransportKind};
+use crate::models::mqtt::{
+    ConnectionInputMode, MqttLoginData, ProxyKind, TlsVerificationMode, TransportKind,
+};
+use crate::utils::formatting::{format_color_hex, parse_color_hex};
 
 #[derive(Clone, Debug)]
 pub(crate) struct ProfileEntry {
@@ -13,12 +16,14 @@ pub(crate) struct ProfileEntry {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-struct LoginTemplateFile {
+pub(crate) struct LoginTemplateFile {
     #[serde(default)]
     profile_name: Option<String>,
     #[serde(default)]
     name: String,
     #[serde(default)]
+    group: String,
+    #[serde(default)]
     broker: String,
     #[serde(default)]
     port: String,
@@ -27,6 +32,8 @@ struct LoginTemplateFile {
     #[serde(default)]
     client_id: String,
     #[serde(default)]
+    request_assigned_client_id: bool,
+    #[serde(default)]
     keep_alive_secs: u16,
     #[serde(default)]
     testament_and_last_will: String,
@@ -37,6 +44,8 @@ struct LoginTemplateFile {
     #[serde(default)]
     testament_retain: bool,
     #[serde(default)]
+    testament_on_quit: bool,
+    #[serde(default)]
     connection_mode: ConnectionInputMode,
     #[serde(default)]
     connection_url: String,
@@ -45,53 +54,124 @@ struct LoginTemplateFile {
     #[serde(default = "default_ws_path")]
     ws_path: String,
     #[serde(default)]
+    unix_socket_path: String,
+    #[serde(default)]
     tls_verification: TlsVerificationMode,
     #[serde(default)]
     tls_ca_cert_path: String,
+    #[serde(default)]
+    tls_client_cert_path: String,
+    #[serde(default)]
+    tls_client_key_path: String,
+    #[serde(default)]
+    tls_alpn: String,
+    #[serde(default)]
+    tls_sni: String,
+    #[serde(default)]
+    tls_pin_certificate: bool,
+    #[serde(default)]
+    tls_pinned_sha256: String,
+    #[serde(default)]
+    color_tag: String,
+    #[serde(default)]
+    confirm_before_publish: bool,
+    #[serde(default)]
+    read_only: bool,
+    #[serde(default)]
+    proxy_kind: ProxyKind,
+    #[serde(default)]
+    proxy_host: String,
+    #[serde(default)]
+    proxy_port: String,
+    #[serde(default)]
+    proxy_username: String,
+    #[serde(default)]
+    source_address: String,
+    #[serde(default)]
+    auto_subscribe: Vec<(String, u8)>,
 }
 
 impl LoginTemplateFile {
-    fn from_login(profile_name: Option<String>, login: &MqttLoginData) -> Self {
+    pub(crate) fn from_login(profile_name: Option<String>, login: &MqttLoginData) -> Self {
         Self {
             profile_name,
             name: login.name.clone(),
+            group: login.group.clone(),
             broker: login.broker.clone(),
             port: login.port.clone(),
             username: login.username.clone(),
             client_id: login.client_id.clone(),
+            request_assigned_client_id: login.request_assigned_client_id,
             keep_alive_secs: login.effective_keep_alive_secs(),
             testament_and_last_will: login.testament_and_last_will.clone(),
             testament_topic: login.testament_topic.clone(),
             testament_qos: login.testament_qos,
             testament_retain: login.testament_retain,
+            testament_on_quit: login.testament_on_quit,
             connection_mode: login.connection_mode,
             connection_url: login.connection_url.clone(),
             transport: login.transport,
             ws_path: login.ws_path.clone(),
+            unix_socket_path: login.unix_socket_path.clone(),
             tls_verification: login.tls_verification,
             tls_ca_cert_path: login.tls_ca_cert_path.clone(),
+            tls_client_cert_path: login.tls_client_cert_path.clone(),
+            tls_client_key_path: login.tls_client_key_path.clone(),
+            tls_alpn: login.tls_alpn.clone(),
+            tls_sni: login.tls_sni.clone(),
+            tls_pin_certificate: login.tls_pin_certificate,
+            tls_pinned_sha256: login.tls_pinned_sha256.clone(),
+            color_tag: login.color_tag.map(format_color_hex).unwrap_or_default(),
+            confirm_before_publish: login.confirm_before_publish,
+            read_only: login.read_only,
+            proxy_kind: login.proxy_kind,
+            proxy_host: login.proxy_host.clone(),
+            proxy_port: login.proxy_port.clone(),
+            proxy_username: login.proxy_username.clone(),
+            source_address: login.source_address.clone(),
+            auto_subscribe: login.auto_subscribe.clone(),
         }
     }
 
-    fn into_login(self) -> MqttLoginData {
+    pub(crate) fn into_login(self) -> MqttLoginData {
         MqttLoginData {
             name: self.name,
+            group: self.group,
             broker: self.broker,
             port: self.port,
             username: self.username,
             password: String::new(),
             client_id: self.client_id,
+            request_assigned_client_id: self.request_assigned_client_id,
             keep_alive_secs: self.keep_alive_secs.max(1),
             testament_and_last_will: self.testament_and_last_will,
             testament_topic: self.testament_topic,
             testament_qos: self.testament_qos,
             testament_retain: self.testament_retain,
+            testament_on_quit: self.testament_on_quit,
             connection_mode: self.connection_mode,
             connection_url: self.connection_url,
             transport: self.transport,
             ws_path: self.ws_path,
+            unix_socket_path: self.unix_socket_path,
             tls_verification: self.tls_verification,
             tls_ca_cert_path: self.tls_ca_cert_path,
+            tls_client_cert_path: self.tls_client_cert_path,
+            tls_client_key_path: self.tls_client_key_path,
+            tls_alpn: self.tls_alpn,
+            tls_sni: self.tls_sni,
+            tls_pin_certificate: self.tls_pin_certificate,
+            tls_pinned_sha256: self.tls_pinned_sha256,
+            color_tag: parse_color_hex(&self.color_tag),
+            confirm_before_publish: self.confirm_before_publish,
+            read_only: self.read_only,
+            proxy_kind: self.proxy_kind,
+            proxy_host: self.proxy_host,
+            proxy_port: self.proxy_port,
+            proxy_username: self.proxy_username,
+            proxy_password: String::new(),
+            source_address: self.source_address,
+            auto_subscribe: self.auto_subscribe,
         }
     }
 }
@@ -208,7 +288,7 @@ fn default_ws_path() -> String {
 #[cfg(test)]
 mod tests {
     use super::LoginTemplateFile;
-    use crate::models::mqtt::{ConnectionInputMode, TlsVerificationMode, TransportKind};
+    use crate::models::mqtt::{ConnectionInputMode, ProxyKind, TlsVerificationMode, TransportKind};
 
     #[test]
     fn old_profiles_load_with_transport_defaults() {
@@ -228,6 +308,9 @@ keep_alive_secs = 30
         assert_eq!(login.ws_path, "/mqtt");
         assert_eq!(login.tls_verification, TlsVerificationMode::SystemRoots);
         assert!(login.tls_ca_cert_path.is_empty());
+        assert_eq!(login.color_tag, None);
+        assert!(!login.confirm_before_publish);
+        assert_eq!(login.proxy_kind, ProxyKind::None);
     }
 
     #[test]
@@ -235,21 +318,40 @@ keep_alive_secs = 30
         let template = LoginTemplateFile {
             profile_name: Some("secure".to_string()),
             name: "Secure Broker".to_string(),
+            group: "prod".to_string(),
             broker: "broker.example.com".to_string(),
             port: "443".to_string(),
             username: "alice".to_string(),
             client_id: "client-1".to_string(),
+            request_assigned_client_id: false,
             keep_alive_secs: 45,
             testament_and_last_will: "bye".to_string(),
             testament_topic: "last/will".to_string(),
             testament_qos: 1,
             testament_retain: true,
+            testament_on_quit: true,
             connection_mode: ConnectionInputMode::Url,
             connection_url: "wss://broker.example.com/mqtt".to_string(),
             transport: TransportKind::Wss,
             ws_path: "/mqtt".to_string(),
+            unix_socket_path: String::new(),
             tls_verification: TlsVerificationMode::CustomCa,
             tls_ca_cert_path: "/tmp/ca.pem".to_string(),
+            tls_client_cert_path: "/tmp/client.pem".to_string(),
+            tls_client_key_path: "/tmp/client.key".to_string(),
+            tls_alpn: "x-amzn-mqtt-ca".to_string(),
+            tls_sni: "broker.internal".to_string(),
+            tls_pin_certificate: true,
+            tls_pinned_sha256: "ab".repeat(32),
+            color_tag: "#d03030".to_string(),
+            confirm_before_publish: true,
+            read_only: true,
+            proxy_kind: ProxyKind::Socks5,
+            proxy_host: "proxy.corp.example".to_string(),
+            proxy_port: "1080".to_string(),
+            proxy_username: "bob".to_string(),
+            source_address: "192.0.2.10".to_string(),
+            auto_subscribe: vec![("sensors/#".to_string(), 1), ("$SYS/#".to_string(), 0)],
         };
 
         let serialized = toml::to_string_pretty(&template).unwrap();
@@ -267,6 +369,25 @@ keep_alive_secs = 30
             TlsVerificationMode::CustomCa
         );
         assert_eq!(round_tripped.tls_ca_cert_path, "/tmp/ca.pem");
+        assert_eq!(round_tripped.tls_client_cert_path, "/tmp/client.pem");
+        assert_eq!(round_tripped.tls_client_key_path, "/tmp/client.key");
+        assert_eq!(round_tripped.tls_alpn, "x-amzn-mqtt-ca");
+        assert_eq!(round_tripped.tls_sni, "broker.internal");
+        assert_eq!(round_tripped.group, "prod");
+        assert!(round_tripped.tls_pin_certificate);
+        assert!(round_tripped.read_only);
+        assert_eq!(round_tripped.tls_pinned_sha256, "ab".repeat(32));
+        assert_eq!(round_tripped.proxy_kind, ProxyKind::Socks5);
+        assert_eq!(round_tripped.proxy_host, "proxy.corp.example");
+        assert_eq!(round_tripped.source_address, "192.0.2.10");
+        assert!(round_tripped.testament_on_quit);
+        let login = round_tripped.into_login();
+        assert_eq!(
+            login.auto_subscribe,
+            [("sensors/#".to_string(), 1), ("$SYS/#".to_string(), 0)]
+        );
+        assert_eq!(login.color_tag, Some([0xd0, 0x30, 0x30]));
+        assert!(login.proxy_password.is_empty());
     }
 
     #[test]
@@ -274,26 +395,46 @@ keep_alive_secs = 30
         let template = LoginTemplateFile {
             profile_name: Some("dev".to_string()),
             name: "Dev Broker".to_string(),
+            group: String::new(),
             broker: "localhost".to_string(),
             port: "443".to_string(),
             username: String::new(),
             client_id: String::new(),
+            request_assigned_client_id: true,
             keep_alive_secs: 60,
             testament_and_last_will: String::new(),
             testament_topic: String::new(),
             testament_qos: 0,
             testament_retain: false,
+            testament_on_quit: false,
             connection_mode: ConnectionInputMode::Url,
             connection_url: "wss://localhost/mqtt".to_string(),
             transport: TransportKind::Wss,
             ws_path: "/mqtt".to_string(),
+            unix_socket_path: String::new(),
             tls_verification: TlsVerificationMode::InsecureSkipVerify,
             tls_ca_cert_path: String::new(),
+            tls_client_cert_path: String::new(),
+            tls_client_key_path: String::new(),
+            tls_alpn: String::new(),
+            tls_sni: String::new(),
+            tls_pin_certificate: false,
+            tls_pinned_sha256: String::new(),
+            color_tag: String::new(),
+            confirm_before_publish: false,
+            read_only: false,
+            proxy_kind: ProxyKind::Http,
+            proxy_host: String::new(),
+            proxy_port: String::new(),
+            proxy_username: String::new(),
+            source_address: String::new(),
+            auto_subscribe: Vec::new(),
         };
 
         let serialized = toml::to_string_pretty(&template).unwrap();
         assert!(serialized.contains("connection_mode = \"url\""));
         assert!(serialized.contains("transport = \"wss\""));
         assert!(serialized.contains("tls_verification = \"insecure-skip-verify\""));
+        assert!(serialized.contains("proxy_kind = \"http\""));
     }
 }
@@ -1,93 +1,380 @@
-use std::time::SystemTime;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant, SystemTime};
 
 use crate::app::App;
 use crate::app::state::TabState;
-use crate::models::ipc::ClientEvent;
-use crate::models::mqtt::{MAX_STORED_MESSAGES, ReceivedMessage, SubscriptionEntry};
+use crate::models::chart::TopicChart;
+use crate::models::error::ClientError;
+use crate::models::ipc::{ClientCommand, ClientEvent, SkippedTopic};
+use crate::models::latest::LatestByTopic;
+use crate::models::mqtt::{
+    AckMode, ConnectionInfo, EventLogEntry, MAX_EVENT_LOG_ENTRIES, MAX_STORED_MESSAGES,
+    SubscriptionEntry,
+};
+use crate::models::stats::TopicStats;
+use crate::models::sys::SysStats;
 
 pub(crate) fn pump_client_events(app: &mut App) {
+    let explicit_client_ids: Vec<(u64, String, String)> = app
+        .tabs
+        .iter()
+        .filter_map(|tab| {
+            let TabState::Client { mqtt_login, .. } = &tab.state;
+            let client_id = mqtt_login.client_id.trim();
+            (!client_id.is_empty() && !mqtt_login.request_assigned_client_id)
+                .then(|| (tab.id, tab.title.clone(), client_id.to_string()))
+        })
+        .collect();
+
+    let active_tab = app.active_tab;
     for tab in &mut app.tabs {
+        let is_active = active_tab == Some(tab.id);
         let TabState::Client {
+            mqtt_login,
             connection_status,
+            connection_info,
             last_error,
+            disconnect_error,
             subscriptions,
+            rejected_subscriptions,
             messages,
+            message_view,
+            event_log,
+            outbound_log,
+            record_outbound,
             received_count,
+            received_since_connect,
+            unread_count,
+            topic_stats,
+            latest_by_topic,
+            charts,
+            sys_stats,
+            rtt_progress,
+            rtt_result,
             published_count,
+            pending_resubscribe,
+            ack_mode,
+            ack_delay_ms,
             ..
         } = &mut tab.state;
 
         let Some(client) = app.clients.get_mut(&tab.id) else {
             continue;
         };
+        let mut recorder = app.recorders.get_mut(&tab.id);
+        let mut record_error = None;
 
         loop {
             match client.event_rx.try_recv() {
                 Ok(ClientEvent::Status(status)) => {
+                    push_log(event_log, status.clone());
                     *connection_status = status;
                 }
                 Ok(ClientEvent::Error(err)) => {
-                    *last_error = Some(err);
+                    push_log(event_log, format!("{}: {err}", err.label()));
+                    *last_error = Some(err.to_string());
+                }
+                Ok(ClientEvent::Log(text)) => {
+                    push_log(event_log, text);
+                }
+                Ok(ClientEvent::PacketSent(summary)) => {
+                    if *record_outbound {
+                        push_log(outbound_log, summary);
+                    }
                 }
                 Ok(ClientEvent::Connected) => {
                     *connection_status = "Connected".to_string();
+                    if let Some(previous) = connection_info.assigned_client_id.take()
+                        && let Some(base) = tab.title.strip_suffix(&format!(" ({previous})"))
+                    {
+                        tab.title.truncate(base.len());
+                    }
+                    *connection_info = ConnectionInfo::default();
+                    *received_since_connect = 0;
                     *last_error = None;
+                    *disconnect_error = None;
+                    if *ack_mode != AckMode::Auto {
+                        let _ = client.command_tx.send(ClientCommand::SetAckMode {
+                            mode: *ack_mode,
+                            delay: Duration::from_millis(*ack_delay_ms),
+                        });
+                    }
+                    if !pending_resubscribe.is_empty() {
+                        let _ = client.command_tx.send(ClientCommand::SubscribeMany {
+                            entries: pending_resubscribe
+                                .drain(..)
+                                .map(|entry| (entry.topic, entry.qos))
+                                .collect(),
+                        });
+                    }
                 }
-                Ok(ClientEvent::Disconnected(msg)) => {
-                    *connection_status = "Disconnected".to_string();
-                    *last_error = Some(msg);
+                Ok(ClientEvent::Disconnected(err)) => {
+                    *connection_status = match err {
+                        ClientError::ClosedByUser(_) => "Disconnected".to_string(),
+                        ClientError::AuthFailed(_) => "Authentication failed".to_string(),
+                        _ => format!("Disconnected ({})", err.label()),
+                    };
+                    push_log(event_log, format!("{}: {err}", err.label()));
+                    *last_error = Some(err.to_string());
+                    if !matches!(err, ClientError::ClosedByUser(_)) {
+                        app.toasts.push(
+                            tab.id,
+                            format!("{}: {}", tab.title, err.label()),
+                            err.to_string(),
+                            Instant::now(),
+                        );
+                    }
+                    if let ClientError::SessionTakenOver { client_id } = &err
+                        && let Some((_, title, _)) = explicit_client_ids
+                            .iter()
+                            .find(|(id, _, other)| *id != tab.id && other == client_id)
+                    {
+                        *last_error = Some(format!("{err} (tab '{title}' uses the same ID)"));
+                    }
+                    *disconnect_error = Some(err);
+                    *rtt_progress = None;
+                }
+                Ok(ClientEvent::KeepAlive { secs, from_server }) => {
+                    connection_info.keep_alive_secs = Some(secs);
+                    connection_info.keep_alive_from_server = from_server;
+                    if from_server {
+                        push_log(
+                            event_log,
+                            format!("Broker set keep-alive to {secs}s (server keep-alive)"),
+                        );
+                    } else {
+                        push_log(event_log, format!("Keep-alive {secs}s"));
+                    }
+                }
+                Ok(ClientEvent::PingResponse) => {
+                    connection_info.last_ping_response = Some(SystemTime::now());
+                    push_log(event_log, "PINGRESP received".to_string());
+                }
+                Ok(ClientEvent::AssignedClientId(client_id)) => {
+                    push_log(
+                        event_log,
+                        format!("Broker assigned client ID '{client_id}'"),
+                    );
+                    tab.title = format!("{} ({client_id})", tab.title);
+                    connection_info.assigned_client_id = Some(client_id);
+                }
+                Ok(ClientEvent::TlsSession(session)) => {
+                    push_log(event_log, format!("TLS session: {}", session.summary()));
+                    if mqtt_login.tls_pin_certificate
+                        && mqtt_login.tls_pinned_sha256_opt().is_none()
+                        && let Some(fingerprint) = &session.certificate_sha256
+                    {
+                        push_log(
+                            event_log,
+                            format!("Pinned broker certificate SHA-256 {fingerprint}"),
+                        );
+                        mqtt_login.tls_pinned_sha256 = fingerprint.clone();
+                    }
+                    connection_info.tls_session = Some(session);
+                }
+                Ok(ClientEvent::MaximumQos(maximum_qos)) => {
+                    push_log(event_log, format!("Broker maximum QoS is {maximum_qos}"));
+                    connection_info.maximum_qos = Some(maximum_qos);
                 }
                 Ok(ClientEvent::Subscribed {
                     topic,
-                    qos,
+                    qos: requested_qos,
+                    granted_qos,
+                    packet_id,
                     details,
+                    ack_elapsed,
                 }) => {
+                    let qos = granted_qos;
+                    rejected_subscriptions.retain(|(rejected, _)| rejected != &topic);
                     if let Some(entry) = subscriptions.iter_mut().find(|entry| entry.topic == topic)
                     {
                         entry.qos = qos;
+                        entry.packet_id = Some(packet_id);
                     } else {
                         subscriptions.push(SubscriptionEntry {
                             topic: topic.clone(),
                             qos,
+                            packet_id: Some(packet_id),
                         });
                     }
-                    *connection_status = format!("Subscribed to '{topic}'");
+                    let ack = format!("(ack in {}ms)", ack_elapsed.as_millis());
+                    *connection_status = if granted_qos < requested_qos {
+                        format!(
+                            "Subscribed to '{topic}' (packet id {packet_id}) {ack}: requested QoS {requested_qos}, granted {granted_qos}"
+                        )
+                    } else {
+                        format!("Subscribed to '{topic}' (packet id {packet_id}) {ack}")
+                    };
                     *last_error = Some(format!("SUBACK: {details}"));
                 }
-                Ok(ClientEvent::Unsubscribed { topic, details }) => {
+                Ok(ClientEvent::SubscribeFailed {
+                    topic,
+                    packet_id,
+                    reason,
+                }) => {
+                    push_log(
+                        event_log,
+                        format!(
+                            "Subscription to '{topic}' rejected (packet id {packet_id}): {reason}"
+                        ),
+                    );
+                    *connection_status = format!("Subscription to '{topic}' rejected");
+                    *last_error = Some(format!("SUBACK: {reason}"));
+                    rejected_subscriptions.retain(|(rejected, _)| rejected != &topic);
+                    rejected_subscriptions.push((topic, reason));
+                }
+                Ok(ClientEvent::Unsubscribed {
+                    topic,
+                    packet_id,
+                    details,
+                }) => {
                     subscriptions.retain(|entry| entry.topic != topic);
-                    *connection_status = format!("Unsubscribed from '{topic}'");
+                    *connection_status =
+                        format!("Unsubscribed from '{topic}' (packet id {packet_id})");
                     *last_error = Some(format!("UNSUBACK: {details}"));
                 }
-                Ok(ClientEvent::Published { topic, packet_id }) => {
+                Ok(ClientEvent::Published {
+                    topic,
+                    packet_id,
+                    request,
+                    ack_elapsed,
+                }) => {
                     *published_count += 1;
+                    let mut status = match request {
+                        Some(request) => format!("Published request #{request} to '{topic}'"),
+                        None => format!("Published to '{topic}'"),
+                    };
                     if let Some(id) = packet_id {
-                        *connection_status = format!("Published to '{topic}' (packet id {id})");
-                    } else {
-                        *connection_status = format!("Published to '{topic}'");
+                        status.push_str(&format!(" (packet id {id})"));
+                    }
+                    if let Some(elapsed) = ack_elapsed {
+                        status.push_str(&format!(" (ack in {}ms)", elapsed.as_millis()));
+                    }
+                    push_log(event_log, status.clone());
+                    *connection_status = status;
+                }
+                Ok(ClientEvent::MessageBatch(batch)) => {
+                    let count = batch.len() as u64;
+                    *received_count += count;
+                    *received_since_connect += count;
+                    if !is_active {
+                        *unread_count += count;
                     }
+                    for message in &batch {
+                        topic_stats.record(&message.topic, &message.payload, message.timestamp);
+                        latest_by_topic.record(message);
+                        sys_stats.record(&message.topic, &message.payload, message.timestamp);
+                        for chart in charts
+                            .iter_mut()
+                            .filter(|chart| chart.topic == message.topic)
+                        {
+                            chart.record(&message.payload, message.timestamp);
+                        }
+                        if let Some(active) = recorder.as_deref_mut()
+                            && let Err(err) = active.record(message)
+                        {
+                            record_error = Some(err);
+                            recorder = None;
+                        }
+                    }
+                    messages.extend(batch);
+                    let overflow = messages.len().saturating_sub(MAX_STORED_MESSAGES);
+                    messages.drain(..overflow);
                 }
-                Ok(ClientEvent::MessageReceived {
-                    topic,
-                    qos,
-                    retain,
-                    payload,
-                }) => {
-                    *received_count += 1;
-                    messages.push_back(ReceivedMessage {
-                        timestamp: SystemTime::now(),
-                        topic,
-                        qos,
-                        retain,
-                        payload,
-                    });
-                    while messages.len() > MAX_STORED_MESSAGES {
-                        let _ = messages.pop_front();
+                Ok(ClientEvent::MessagesSkipped(skipped)) => {
+                    let count: u64 = skipped.iter().map(|skipped| skipped.count).sum();
+                    *received_count += count;
+                    *received_since_connect += count;
+                    if !is_active {
+                        *unread_count += count;
                     }
+                    record_skipped(&skipped, topic_stats, latest_by_topic, sys_stats, charts);
+                    push_log(
+                        event_log,
+                        format!("{count} messages skipped while the window was minimised"),
+                    );
+                }
+                Ok(ClientEvent::Acked { packet_id }) => {
+                    if let Some(message) = messages
+                        .iter_mut()
+                        .rev()
+                        .find(|message| message.ack_pending && message.packet_id == Some(packet_id))
+                    {
+                        message.ack_pending = false;
+                        message_view.invalidate();
+                    }
+                }
+                Ok(ClientEvent::RttSample(rtt)) => {
+                    rtt_progress.get_or_insert_default().record(rtt);
+                }
+                Ok(ClientEvent::RttFinished(summary)) => {
+                    let text = match summary.average() {
+                        Some(average) => format!(
+                            "RTT min/avg/max {}/{}/{}ms, {} lost",
+                            summary.min.as_millis(),
+                            average.as_millis(),
+                            summary.max.as_millis(),
+                            summary.lost
+                        ),
+                        None => format!("RTT measurement failed, all {} pings lost", summary.lost),
+                    };
+                    push_log(event_log, text);
+                    *rtt_progress = None;
+                    *rtt_result = Some(summary);
                 }
                 Err(std::sync::mpsc::TryRecvError::Empty) => break,
                 Err(std::sync::mpsc::TryRecvError::Disconnected) => break,
             }
         }
+
+        if let Some(active) = recorder
+            && let Err(err) = active.flush()
+        {
+            record_error = Some(err);
+        }
+        if let Some(err) = record_error {
+            app.recorders.remove(&tab.id);
+            push_log(event_log, format!("Recording stopped: {err}"));
+            *last_error = Some(err);
+        }
+    }
+}
+
+/// Folds messages the client dropped while the window was hidden into the per-topic views,
+/// so a topic seen only then still shows up in the stats, topic tree and dashboard.
+pub(crate) fn record_skipped(
+    skipped: &[SkippedTopic],
+    topic_stats: &mut TopicStats,
+    latest_by_topic: &mut LatestByTopic,
+    sys_stats: &mut SysStats,
+    charts: &mut [TopicChart],
+) {
+    for skipped in skipped {
+        let latest = &skipped.latest;
+        topic_stats.record_many(
+            &latest.topic,
+            &latest.payload,
+            skipped.count,
+            skipped.bytes,
+            latest.timestamp,
+        );
+        latest_by_topic.record(latest);
+        sys_stats.record(&latest.topic, &latest.payload, latest.timestamp);
+        for chart in charts
+            .iter_mut()
+            .filter(|chart| chart.topic == latest.topic)
+        {
+            chart.record(&latest.payload, latest.timestamp);
+        }
+    }
+}
+
+pub(crate) fn push_log(event_log: &mut VecDeque<EventLogEntry>, text: String) {
+    event_log.push_back(EventLogEntry {
+        timestamp: SystemTime::now(),
+        text,
+    });
+    while event_log.len() > MAX_EVENT_LOG_ENTRIES {
+        let _ = event_log.pop_front();
     }
 }
@@ -0,0 +1,49 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::models::export::ExportedMessage;
+use crate::models::mqtt::ReceivedMessage;
+
+#[derive(Debug)]
+pub(crate) struct MessageRecorder {
+    pub(crate) path: PathBuf,
+    pub(crate) bytes_written: u64,
+    writer: BufWriter<File>,
+}
+
+impl MessageRecorder {
+    /// Starts a new capture at `path`, replacing anything already there as the save dialog
+    /// that picked it implies.
+    pub(crate) fn create(path: &Path) -> Result<Self, String> {
+        let file = File::options()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(path)
+            .map_err(|err| format!("Failed to open {}: {err}", path.display()))?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            bytes_written: 0,
+            writer: BufWriter::new(file),
+        })
+    }
+
+    pub(crate) fn record(&mut self, msg: &ReceivedMessage) -> Result<(), String> {
+        let mut line = serde_json::to_string(&ExportedMessage::from_message(msg))
+            .map_err(|err| format!("Failed to serialize message: {err}"))?;
+        line.push('\n');
+        self.writer
+            .write_all(line.as_bytes())
+            .map_err(|err| format!("Failed to write to {}: {err}", self.path.display()))?;
+        self.bytes_written += line.len() as u64;
+        Ok(())
+    }
+
+    pub(crate) fn flush(&mut self) -> Result<(), String> {
+        self.writer
+            .flush()
+            .map_err(|err| format!("Failed to write to {}: {err}", self.path.display()))
+    }
+}
@@ -0,0 +1,158 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::config_profiles::LoginTemplateFile;
+use crate::app::settings::config_dir;
+use crate::models::message_view::FlagFilter;
+use crate::models::protobuf::ProtobufMapping;
+use crate::utils::formatting::{PayloadDecoder, PayloadEncoding};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct SessionFile {
+    #[serde(default)]
+    pub(crate) tabs: Vec<SessionTab>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct SessionTab {
+    pub(crate) title: String,
+    pub(crate) login: LoginTemplateFile,
+    #[serde(default)]
+    pub(crate) subscriptions: Vec<SessionSubscription>,
+    #[serde(default)]
+    pub(crate) view: SessionView,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct SessionView {
+    pub(crate) topic_filter: String,
+    pub(crate) flag_filter: FlagFilter,
+    pub(crate) muted_topics: Vec<String>,
+    pub(crate) json_columns: Vec<String>,
+    pub(crate) dashboard_topics: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) protobuf_schema: Option<PathBuf>,
+    pub(crate) protobuf_mappings: Vec<ProtobufMapping>,
+    pub(crate) payload_encoding: PayloadEncoding,
+    pub(crate) payload_decoder: PayloadDecoder,
+    /// Written by older versions before the decoder selector replaced the Pretty JSON toggle.
+    #[serde(skip_serializing)]
+    pub(crate) pretty_json: bool,
+    pub(crate) collapse_duplicates: bool,
+    pub(crate) interpret_numbers: bool,
+    pub(crate) clear_on_reconnect: bool,
+    pub(crate) max_messages: usize,
+    pub(crate) subscribe_qos: u8,
+    pub(crate) publish_qos: u8,
+}
+
+impl Default for SessionView {
+    fn default() -> Self {
+        Self {
+            topic_filter: String::new(),
+            flag_filter: FlagFilter::default(),
+            muted_topics: Vec::new(),
+            json_columns: Vec::new(),
+            dashboard_topics: Vec::new(),
+            protobuf_schema: None,
+            protobuf_mappings: Vec::new(),
+            payload_encoding: PayloadEncoding::default(),
+            payload_decoder: PayloadDecoder::default(),
+            pretty_json: false,
+            collapse_duplicates: false,
+            interpret_numbers: true,
+            clear_on_reconnect: false,
+            max_messages: 200,
+            subscribe_qos: 0,
+            publish_qos: 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct SessionSubscription {
+    pub(crate) topic: String,
+    #[serde(default)]
+    pub(crate) qos: u8,
+}
+
+pub(crate) fn load_session() -> Result<SessionFile, String> {
+    let path = session_path()?;
+    if !path.exists() {
+        return Ok(SessionFile::default());
+    }
+
+    let contents = fs::read_to_string(&path)
+        .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+    toml::from_str(&contents)
+        .map_err(|err| format!("Failed to parse TOML {}: {err}", path.display()))
+}
+
+pub(crate) fn save_session(session: &SessionFile) -> Result<(), String> {
+    let path = session_path()?;
+    let serialized = toml::to_string_pretty(session)
+        .map_err(|err| format!("Failed to serialize session: {err}"))?;
+
+    fs::write(&path, serialized)
+        .map_err(|err| format!("Failed to write session {}: {err}", path.display()))
+}
+
+fn session_path() -> Result<PathBuf, String> {
+    Ok(config_dir()?.join("session.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SessionFile, SessionSubscription, SessionTab, SessionView};
+    use crate::app::config_profiles::LoginTemplateFile;
+    use crate::models::mqtt::MqttLoginData;
+
+    #[test]
+    fn session_round_trips_tabs_without_passwords() {
+        let login = MqttLoginData {
+            broker: "broker.example.com".to_string(),
+            password: "secret".to_string(),
+            ..MqttLoginData::default()
+        };
+
+        let session = SessionFile {
+            tabs: vec![SessionTab {
+                title: "Prod".to_string(),
+                login: LoginTemplateFile::from_login(None, &login),
+                subscriptions: vec![SessionSubscription {
+                    topic: "sensors/#".to_string(),
+                    qos: 1,
+                }],
+                view: SessionView {
+                    topic_filter: "room1".to_string(),
+                    max_messages: 50,
+                    ..SessionView::default()
+                },
+            }],
+        };
+
+        let serialized = toml::to_string_pretty(&session).unwrap();
+        assert!(!serialized.contains("secret"));
+
+        let mut restored = toml::from_str::<SessionFile>(&serialized).unwrap();
+        let tab = restored.tabs.pop().unwrap();
+        assert_eq!(tab.title, "Prod");
+        assert_eq!(tab.subscriptions[0].topic, "sensors/#");
+        assert_eq!(tab.subscriptions[0].qos, 1);
+        assert_eq!(tab.view.topic_filter, "room1");
+        assert_eq!(tab.view.max_messages, 50);
+        assert_eq!(tab.login.into_login().broker, "broker.example.com");
+    }
+
+    #[test]
+    fn sessions_without_view_state_use_tab_defaults() {
+        let restored = toml::from_str::<SessionFile>(
+            "[[tabs]]\ntitle = \"Old\"\n[tabs.login]\nbroker = \"localhost\"\n",
+        )
+        .unwrap();
+        assert_eq!(restored.tabs[0].view, SessionView::default());
+    }
+}
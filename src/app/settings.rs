@@ -0,0 +1,226 @@
+use std::fs;
+use std::path::PathBuf;
+
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+pub(crate) const MIN_UI_SCALE: f32 = 0.5;
+pub(crate) const MAX_UI_SCALE: f32 = 3.0;
+pub(crate) const DEFAULT_COMMAND_QUEUE_LIMIT: usize = 1000;
+pub(crate) const DEFAULT_CLIENT_SOFT_LIMIT: usize = 20;
+pub(crate) const DEFAULT_WORKER_THREADS: usize = 2;
+pub(crate) const MAX_WORKER_THREADS: usize = 64;
+pub(crate) const DEFAULT_TOPIC_TREE_MAX_DEPTH: usize = 8;
+
+const DEFAULT_WINDOW_SIZE: [f32; 2] = [1100.0, 760.0];
+const MIN_WINDOW_SIZE: [f32; 2] = [320.0, 240.0];
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct AppSettings {
+    pub(crate) ui_scale: f32,
+    pub(crate) restore_session: bool,
+    pub(crate) auto_connect_restored_tabs: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) command_queue_limit: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) client_soft_limit: Option<usize>,
+    /// Caps tokio worker threads; `None` keeps one worker per core.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) worker_threads: Option<usize>,
+    /// Topic tree levels shown before the rest of a topic is folded into one leaf.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) topic_tree_max_depth: Option<usize>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub(crate) quick_subscribe_topic: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub(crate) broker_timestamp_property: String,
+    /// Pre-filled into the Subscribe and Publish fields of new tabs; may be empty.
+    pub(crate) new_tab_subscribe_topic: String,
+    pub(crate) new_tab_publish_topic: String,
+    pub(crate) new_tab_publish_payload: String,
+    /// QoS levels new tabs start with, for setups that never use QoS 0.
+    pub(crate) new_tab_subscribe_qos: u8,
+    pub(crate) new_tab_publish_qos: u8,
+    /// Lists tabs in a left sidebar instead of the horizontal tab bar.
+    pub(crate) tab_sidebar: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) window: Option<WindowGeometry>,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            ui_scale: 1.0,
+            restore_session: false,
+            auto_connect_restored_tabs: false,
+            command_queue_limit: None,
+            client_soft_limit: None,
+            worker_threads: None,
+            topic_tree_max_depth: None,
+            quick_subscribe_topic: String::new(),
+            broker_timestamp_property: String::new(),
+            new_tab_subscribe_topic: "t1".to_string(),
+            new_tab_publish_topic: "t1".to_string(),
+            new_tab_publish_payload: "hello".to_string(),
+            new_tab_subscribe_qos: 0,
+            new_tab_publish_qos: 0,
+            tab_sidebar: false,
+            window: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct WindowGeometry {
+    pub(crate) inner_size: [f32; 2],
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) position: Option<[f32; 2]>,
+}
+
+impl WindowGeometry {
+    fn is_usable(&self) -> bool {
+        self.inner_size
+            .iter()
+            .zip(MIN_WINDOW_SIZE)
+            .all(|(value, min)| value.is_finite() && *value >= min)
+    }
+}
+
+impl AppSettings {
+    pub(crate) fn effective_ui_scale(&self) -> f32 {
+        if self.ui_scale.is_finite() {
+            self.ui_scale.clamp(MIN_UI_SCALE, MAX_UI_SCALE)
+        } else {
+            1.0
+        }
+    }
+
+    pub(crate) fn effective_worker_threads(&self) -> Option<usize> {
+        self.worker_threads
+            .map(|threads| threads.clamp(1, MAX_WORKER_THREADS))
+    }
+
+    pub(crate) fn initial_window_size(&self) -> [f32; 2] {
+        self.window
+            .filter(WindowGeometry::is_usable)
+            .map(|window| window.inner_size)
+            .unwrap_or(DEFAULT_WINDOW_SIZE)
+    }
+
+    pub(crate) fn initial_window_position(&self) -> Option<[f32; 2]> {
+        self.window
+            .filter(WindowGeometry::is_usable)
+            .and_then(|window| window.position)
+            .filter(|position| position.iter().all(|value| value.is_finite()))
+    }
+}
+
+pub(crate) fn load_settings() -> Result<AppSettings, String> {
+    let path = settings_path()?;
+    if !path.exists() {
+        return Ok(AppSettings::default());
+    }
+
+    let contents = fs::read_to_string(&path)
+        .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+    toml::from_str(&contents)
+        .map_err(|err| format!("Failed to parse TOML {}: {err}", path.display()))
+}
+
+pub(crate) fn save_settings(settings: &AppSettings) -> Result<(), String> {
+    let path = settings_path()?;
+    let serialized = toml::to_string_pretty(settings)
+        .map_err(|err| format!("Failed to serialize settings: {err}"))?;
+
+    fs::write(&path, serialized)
+        .map_err(|err| format!("Failed to write settings {}: {err}", path.display()))
+}
+
+pub(crate) fn config_dir() -> Result<PathBuf, String> {
+    let project_dirs = ProjectDirs::from("io", "jotrorox", "mqui")
+        .ok_or_else(|| "Could not resolve operating system config directory".to_string())?;
+
+    let dir = project_dirs.config_dir().to_path_buf();
+    fs::create_dir_all(&dir)
+        .map_err(|err| format!("Failed to create config directory {}: {err}", dir.display()))?;
+    Ok(dir)
+}
+
+fn settings_path() -> Result<PathBuf, String> {
+    Ok(config_dir()?.join("settings.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AppSettings, DEFAULT_WINDOW_SIZE, WindowGeometry};
+
+    #[test]
+    fn missing_fields_fall_back_to_defaults() {
+        let settings = toml::from_str::<AppSettings>("").unwrap();
+        assert_eq!(settings, AppSettings::default());
+    }
+
+    #[test]
+    fn empty_new_tab_defaults_round_trip() {
+        let settings = AppSettings {
+            new_tab_publish_payload: String::new(),
+            ..AppSettings::default()
+        };
+        let serialized = toml::to_string_pretty(&settings).unwrap();
+        let round_tripped = toml::from_str::<AppSettings>(&serialized).unwrap();
+        assert_eq!(round_tripped.new_tab_publish_payload, "");
+        assert_eq!(round_tripped.new_tab_publish_topic, "t1");
+    }
+
+    #[test]
+    fn ui_scale_is_clamped() {
+        let settings = AppSettings {
+            ui_scale: 10.0,
+            ..AppSettings::default()
+        };
+        assert_eq!(settings.effective_ui_scale(), 3.0);
+
+        let settings = AppSettings {
+            ui_scale: f32::NAN,
+            ..AppSettings::default()
+        };
+        assert_eq!(settings.effective_ui_scale(), 1.0);
+    }
+
+    #[test]
+    fn worker_threads_are_clamped() {
+        let mut settings = AppSettings::default();
+        assert_eq!(settings.effective_worker_threads(), None);
+
+        settings.worker_threads = Some(0);
+        assert_eq!(settings.effective_worker_threads(), Some(1));
+        settings.worker_threads = Some(1000);
+        assert_eq!(settings.effective_worker_threads(), Some(64));
+    }
+
+    #[test]
+    fn window_geometry_round_trips_and_rejects_bogus_sizes() {
+        let settings = AppSettings {
+            window: Some(WindowGeometry {
+                inner_size: [800.0, 600.0],
+                position: Some([10.0, 20.0]),
+            }),
+            ..AppSettings::default()
+        };
+        let serialized = toml::to_string_pretty(&settings).unwrap();
+        let round_tripped = toml::from_str::<AppSettings>(&serialized).unwrap();
+        assert_eq!(round_tripped.initial_window_size(), [800.0, 600.0]);
+        assert_eq!(round_tripped.initial_window_position(), Some([10.0, 20.0]));
+
+        let tiny = AppSettings {
+            window: Some(WindowGeometry {
+                inner_size: [0.0, 0.0],
+                position: Some([10.0, 20.0]),
+            }),
+            ..AppSettings::default()
+        };
+        assert_eq!(tiny.initial_window_size(), DEFAULT_WINDOW_SIZE);
+        assert_eq!(tiny.initial_window_position(), None);
+    }
+}
@@ -1,6 +1,20 @@
 use std::collections::VecDeque;
 
-use crate::models::mqtt::{MqttLoginData, ReceivedMessage, SubscriptionEntry};
+use eframe::egui;
+
+use crate::models::chart::TopicChart;
+use crate::models::error::ClientError;
+use crate::models::ipc::ClientCommand;
+use crate::models::latest::LatestByTopic;
+use crate::models::message_view::{ClearedMessages, FlagFilter, MessageView};
+use crate::models::mqtt::{
+    AckMode, ConnectionInfo, EventLogEntry, LoadedPayload, MqttLoginData, ReceivedMessage,
+    SubscriptionEntry,
+};
+use crate::models::protobuf::{ProtobufMapping, ProtobufSchema};
+use crate::models::stats::{RttSummary, TopicStats, TopicStatsSort};
+use crate::models::sys::SysStats;
+use crate::utils::formatting::{PayloadDecoder, PayloadEncoding};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) enum TabKind {
@@ -12,24 +26,82 @@ pub(crate) enum TabState {
     Client {
         mqtt_login: MqttLoginData,
         connection_status: String,
+        connection_info: ConnectionInfo,
         last_error: Option<String>,
+        disconnect_error: Option<ClientError>,
         subscribe_topic: String,
         subscribe_qos: u8,
+        ack_mode: AckMode,
+        ack_delay_ms: u64,
         unsubscribe_topic: String,
         editing_subscription_topic: Option<String>,
         editing_subscription_value: String,
         editing_subscription_qos: u8,
         publish_topic: String,
+        publish_multi_topic: bool,
         publish_qos: u8,
         publish_retain: bool,
+        publish_expiry_enabled: bool,
+        publish_expiry_secs: u32,
+        publish_content_type: String,
+        publish_payload_utf8: bool,
+        publish_response_topic: String,
+        publish_correlation_data: String,
         publish_payload: String,
-        payload_view_hex: bool,
+        publish_payload_blob: Option<LoadedPayload>,
+        pending_publish_confirmation: Vec<ClientCommand>,
+        /// Topic and QoS of a SUBSCRIBE for a topic that is already subscribed, awaiting
+        /// confirmation because it may change the granted QoS.
+        pending_subscribe_confirmation: Option<(String, u8)>,
+        replay_speed: f64,
+        replay_preserve_timing: bool,
+        payload_encoding: PayloadEncoding,
+        payload_decoder: PayloadDecoder,
+        collapse_duplicates: bool,
+        /// Shows bare numeric payloads as dates or normalised numbers next to the raw value.
+        interpret_numbers: bool,
+        /// Starts each manual reconnect with an empty message list instead of keeping history.
+        clear_on_reconnect: bool,
         topic_filter: String,
+        flag_filter: FlagFilter,
+        muted_topics: Vec<String>,
+        mute_topic_input: String,
+        /// JSON paths shown as extra columns in the Messages panel.
+        json_columns: Vec<String>,
+        json_column_input: String,
+        json_column_error: Option<String>,
+        /// Topics shown as latest-value cells in the dashboard.
+        dashboard_topics: Vec<String>,
+        dashboard_topic_input: String,
+        protobuf_schema: Option<ProtobufSchema>,
+        protobuf_mappings: Vec<ProtobufMapping>,
+        protobuf_topic_input: String,
+        protobuf_type_input: String,
+        protobuf_error: Option<String>,
         max_messages: usize,
         subscriptions: Vec<SubscriptionEntry>,
+        /// Topic filters the broker refused in a SUBACK, with the reason.
+        rejected_subscriptions: Vec<(String, String)>,
         messages: VecDeque<ReceivedMessage>,
+        message_view: MessageView,
+        cleared_messages: Option<ClearedMessages>,
+        event_log: VecDeque<EventLogEntry>,
+        outbound_log: VecDeque<EventLogEntry>,
+        record_outbound: bool,
         received_count: u64,
+        received_since_connect: u64,
+        /// Messages received while the tab was not active, shown as a badge in the sidebar.
+        unread_count: u64,
+        topic_stats: TopicStats,
+        latest_by_topic: LatestByTopic,
+        topic_stats_sort: TopicStatsSort,
+        charts: Vec<TopicChart>,
+        sys_stats: SysStats,
+        rtt_ping_count: u32,
+        rtt_progress: Option<RttSummary>,
+        rtt_result: Option<RttSummary>,
         published_count: u64,
+        pending_resubscribe: Vec<SubscriptionEntry>,
     },
 }
 
@@ -37,5 +109,20 @@ pub(crate) enum TabState {
 pub(crate) struct Tab {
     pub(crate) id: u64,
     pub(crate) title: String,
+    pub(crate) color: Option<egui::Color32>,
     pub(crate) state: TabState,
 }
+
+impl Tab {
+    /// The title without the broker-assigned client ID that is appended to it on connect.
+    pub(crate) fn base_title(&self) -> &str {
+        let TabState::Client {
+            connection_info, ..
+        } = &self.state;
+        connection_info
+            .assigned_client_id
+            .as_deref()
+            .and_then(|client_id| self.title.strip_suffix(&format!(" ({client_id})")))
+            .unwrap_or(&self.title)
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::VecDeque;
+use std::sync::mpsc;
+use std::time::Instant;
+
+use mqtt_endpoint_tokio::mqtt_ep;
+
+use crate::client::errors::{describe_mqtt_error, endpoint_failure};
+use crate::models::error::ClientError;
+use crate::models::ipc::ClientEvent;
+
+/// Endpoint options for every connection. The library's automatic PUBACK/PUBREC/PUBREL/PUBCOMP
+/// are turned off, so the tab's ack mode alone decides what is sent and when.
+pub(crate) fn connection_options() -> mqtt_ep::ConnectionOption {
+    mqtt_ep::ConnectionOption::builder()
+        .auto_pub_response(false)
+        .build()
+        .expect("failed to build connection options")
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct InboundAck {
+    pub(crate) packet_id: u16,
+    pub(crate) exactly_once: bool,
+}
+
+impl InboundAck {
+    pub(crate) fn packet_name(self) -> &'static str {
+        if self.exactly_once {
+            "PUBREC"
+        } else {
+            "PUBACK"
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct DelayedAcks {
+    queue: VecDeque<(Instant, InboundAck)>,
+}
+
+impl DelayedAcks {
+    pub(crate) fn push(&mut self, due: Instant, ack: InboundAck) {
+        let index = self
+            .queue
+            .partition_point(|(queued_due, _)| *queued_due <= due);
+        self.queue.insert(index, (due, ack));
+    }
+
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        self.queue.front().map(|(due, _)| *due)
+    }
+
+    pub(crate) fn take_due(&mut self, now: Instant) -> Vec<InboundAck> {
+        let count = self.queue.partition_point(|(due, _)| *due <= now);
+        self.queue.drain(..count).map(|(_, ack)| ack).collect()
+    }
+}
+
+pub(crate) async fn send_inbound_ack(
+    endpoint: &mqtt_ep::endpoint::Endpoint<mqtt_ep::role::Client>,
+    ack: InboundAck,
+    event_tx: &mpsc::Sender<ClientEvent>,
+) {
+    let name = ack.packet_name();
+    let result = if ack.exactly_once {
+        match mqtt_ep::packet::v5_0::Pubrec::builder()
+            .packet_id(ack.packet_id)
+            .build()
+        {
+            Ok(packet) => endpoint.send(packet).await,
+            Err(err) => {
+                let _ = event_tx.send(ClientEvent::Error(ClientError::ProtocolError(format!(
+                    "Failed to build {name}: {}",
+                    describe_mqtt_error(err)
+                ))));
+                return;
+            }
+        }
+    } else {
+        match mqtt_ep::packet::v5_0::Puback::builder()
+            .packet_id(ack.packet_id)
+            .build()
+        {
+            Ok(packet) => endpoint.send(packet).await,
+            Err(err) => {
+                let _ = event_tx.send(ClientEvent::Error(ClientError::ProtocolError(format!(
+                    "Failed to build {name}: {}",
+                    describe_mqtt_error(err)
+                ))));
+                return;
+            }
+        }
+    };
+
+    if result.is_ok() {
+        let _ = event_tx.send(ClientEvent::PacketSent(format!(
+            "{name} id={}",
+            ack.packet_id
+        )));
+    }
+    match result {
+        Ok(()) if ack.exactly_once => {
+            let _ = event_tx.send(ClientEvent::Log(format!(
+                "QoS 2 in: PUBREC sent (packet id {})",
+                ack.packet_id
+            )));
+        }
+        Ok(()) => {}
+        Err(err) => {
+            let _ = event_tx.send(ClientEvent::Error(endpoint_failure(
+                &format!("Failed to send {name}"),
+                &err,
+            )));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::time::{Duration, Instant};
+
+    use mqtt_endpoint_tokio::mqtt_ep;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    use super::{DelayedAcks, InboundAck, connection_options, send_inbound_ack};
+
+    fn ack(packet_id: u16) -> InboundAck {
+        InboundAck {
+            packet_id,
+            exactly_once: false,
+        }
+    }
+
+    #[test]
+    fn delayed_acks_are_released_in_due_order() {
+        let now = Instant::now();
+        let mut acks = DelayedAcks::default();
+        acks.push(now + Duration::from_secs(2), ack(2));
+        acks.push(now + Duration::from_secs(1), ack(1));
+        acks.push(now + Duration::from_secs(3), ack(3));
+
+        assert_eq!(acks.deadline(), Some(now + Duration::from_secs(1)));
+        assert!(acks.take_due(now).is_empty());
+        assert_eq!(
+            acks.take_due(now + Duration::from_secs(2)),
+            [ack(1), ack(2)]
+        );
+        assert_eq!(acks.deadline(), Some(now + Duration::from_secs(3)));
+    }
+
+    #[test]
+    fn no_ack_is_written_until_one_is_sent_by_hand() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let stream = TcpStream::connect(listener.local_addr().unwrap())
+                .await
+                .unwrap();
+            let (mut broker, _) = listener.accept().await.unwrap();
+            let endpoint =
+                mqtt_ep::endpoint::Endpoint::<mqtt_ep::role::Client>::new(mqtt_ep::Version::V5_0);
+            endpoint
+                .attach_with_options(
+                    mqtt_ep::transport::TcpTransport::from_stream(stream),
+                    mqtt_ep::endpoint::Mode::Client,
+                    connection_options(),
+                )
+                .await
+                .unwrap();
+
+            let connect = mqtt_ep::packet::v5_0::Connect::builder()
+                .client_id("acks")
+                .unwrap()
+                .clean_start(true)
+                .build()
+                .unwrap();
+            endpoint.send(connect).await.unwrap();
+            let mut buf = [0u8; 64];
+            let _ = broker.read(&mut buf).await.unwrap();
+            broker
+                .write_all(&[0x20, 0x03, 0x00, 0x00, 0x00])
+                .await
+                .unwrap();
+            let _ = endpoint.recv().await.unwrap();
+
+            // QoS 1 PUBLISH on "t", packet id 7, no properties, payload "x".
+            broker
+                .write_all(&[0x32, 0x07, 0x00, 0x01, b't', 0x00, 0x07, 0x00, b'x'])
+                .await
+                .unwrap();
+            let _ = endpoint.recv().await.unwrap();
+            let silent = tokio::time::timeout(Duration::from_millis(200), broker.read(&mut buf));
+            assert!(silent.await.is_err(), "an ack was sent automatically");
+
+            let (event_tx, _event_rx) = mpsc::channel();
+            send_inbound_ack(&endpoint, ack(7), &event_tx).await;
+            let read = broker.read(&mut buf).await.unwrap();
+            assert_eq!(buf[0], 0x40);
+            assert_eq!(&buf[2..4], &[0x00, 0x07]);
+            assert!(read >= 4);
+        });
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use crate::models::ipc::{ClientEvent, SkippedTopic};
+use crate::models::mqtt::{MAX_STORED_MESSAGES, ReceivedMessage};
+
+/// How often a held-back backlog checks whether the window is visible again.
+pub(crate) const HIDDEN_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Received messages held back while the window is minimised, so a long-hidden window does
+/// not come back to a burst of thousands of events. Only the newest [`MAX_STORED_MESSAGES`]
+/// are kept since the UI would drop the rest anyway; older ones are tallied per topic with
+/// their newest message, so topic stats and the latest value per topic still see them.
+#[derive(Debug, Default)]
+pub(crate) struct HiddenBacklog {
+    messages: VecDeque<ReceivedMessage>,
+    skipped: HashMap<String, SkippedTopic>,
+}
+
+impl HiddenBacklog {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.messages.is_empty() && self.skipped.is_empty()
+    }
+
+    pub(crate) fn push(&mut self, message: ReceivedMessage) {
+        self.messages.push_back(message);
+        while self.messages.len() > MAX_STORED_MESSAGES {
+            let Some(dropped) = self.messages.pop_front() else {
+                break;
+            };
+            let bytes = dropped.payload.len() as u64;
+            match self.skipped.get_mut(&dropped.topic) {
+                Some(skipped) => {
+                    skipped.count += 1;
+                    skipped.bytes += bytes;
+                    skipped.latest = dropped;
+                }
+                None => {
+                    let _ = self.skipped.insert(
+                        dropped.topic.clone(),
+                        SkippedTopic {
+                            count: 1,
+                            bytes,
+                            latest: dropped,
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    /// Sends everything held back, the skipped tallies first so they precede the newest
+    /// messages.
+    pub(crate) fn flush(&mut self, event_tx: &mpsc::Sender<ClientEvent>) {
+        if !self.skipped.is_empty() {
+            let mut skipped: Vec<_> = self.skipped.drain().map(|(_, skipped)| skipped).collect();
+            skipped.sort_by_key(|skipped| skipped.latest.timestamp);
+            let _ = event_tx.send(ClientEvent::MessagesSkipped(skipped));
+        }
+        if !self.messages.is_empty() {
+            let _ = event_tx.send(ClientEvent::MessageBatch(self.messages.drain(..).collect()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::HiddenBacklog;
+    use crate::app::events::record_skipped;
+    use crate::models::ipc::ClientEvent;
+    use crate::models::latest::LatestByTopic;
+    use crate::models::mqtt::{MAX_STORED_MESSAGES, ReceivedMessage};
+    use crate::models::stats::TopicStats;
+    use crate::models::sys::SysStats;
+
+    #[test]
+    fn keeps_the_newest_messages_and_tallies_the_rest() {
+        let mut backlog = HiddenBacklog::default();
+        for index in 0..MAX_STORED_MESSAGES + 5 {
+            backlog.push(ReceivedMessage::for_test(&index.to_string(), ""));
+        }
+
+        let (event_tx, event_rx) = mpsc::channel();
+        backlog.flush(&event_tx);
+        assert!(backlog.is_empty());
+
+        let events: Vec<_> = event_rx.try_iter().collect();
+        assert_eq!(events.len(), 2);
+        let ClientEvent::MessagesSkipped(skipped) = &events[0] else {
+            panic!("expected skipped messages");
+        };
+        assert_eq!(skipped.len(), 5);
+        assert!(skipped.iter().all(|skipped| skipped.count == 1));
+        let ClientEvent::MessageBatch(batch) = &events[1] else {
+            panic!("expected a message batch");
+        };
+        assert_eq!(batch.len(), MAX_STORED_MESSAGES);
+        assert_eq!(batch[0].topic, "5");
+    }
+
+    #[test]
+    fn topics_seen_only_in_the_skipped_part_reach_the_per_topic_views() {
+        let mut backlog = HiddenBacklog::default();
+        backlog.push(ReceivedMessage::for_test("rare", "first"));
+        backlog.push(ReceivedMessage::for_test("rare", "second!"));
+        for _ in 0..MAX_STORED_MESSAGES {
+            backlog.push(ReceivedMessage::for_test("busy", "1"));
+        }
+
+        let (event_tx, event_rx) = mpsc::channel();
+        backlog.flush(&event_tx);
+        let Ok(ClientEvent::MessagesSkipped(skipped)) = event_rx.try_recv() else {
+            panic!("expected skipped messages");
+        };
+
+        let mut topic_stats = TopicStats::default();
+        let mut latest_by_topic = LatestByTopic::default();
+        record_skipped(
+            &skipped,
+            &mut topic_stats,
+            &mut latest_by_topic,
+            &mut SysStats::default(),
+            &mut [],
+        );
+        let stat = topic_stats.get("rare").expect("rare topic is tallied");
+        assert_eq!((stat.count, stat.bytes), (2, 12));
+        assert_eq!(latest_by_topic.get("rare").unwrap().payload, b"second!");
+    }
+}
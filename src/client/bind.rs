@@ -0,0 +1,63 @@
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+
+use tokio::net::{TcpSocket, TcpStream, lookup_host};
+
+/// Connects to `addr` from the local `source` IP, trying each resolved address of the same
+/// family in turn. The OS picks the source port.
+pub(crate) async fn connect_from(addr: &str, source: IpAddr) -> io::Result<TcpStream> {
+    let mut last_err = None;
+    for target in lookup_host(addr).await? {
+        if target.is_ipv4() != source.is_ipv4() {
+            continue;
+        }
+        let socket = if source.is_ipv4() {
+            TcpSocket::new_v4()?
+        } else {
+            TcpSocket::new_v6()?
+        };
+        socket.bind(SocketAddr::new(source, 0))?;
+        match socket.connect(target).await {
+            Ok(stream) => return Ok(stream),
+            Err(err) => last_err = Some(err),
+        }
+    }
+
+    Err(last_err.unwrap_or_else(|| {
+        let family = if source.is_ipv4() { "IPv4" } else { "IPv6" };
+        io::Error::new(
+            io::ErrorKind::AddrNotAvailable,
+            format!("{addr} has no {family} address to match the source address"),
+        )
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    use tokio::net::TcpListener;
+
+    use super::connect_from;
+
+    #[test]
+    fn connects_from_the_chosen_source_address() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap().to_string();
+            let source = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+            let stream = connect_from(&addr, source).await.unwrap();
+            assert_eq!(stream.local_addr().unwrap().ip(), source);
+
+            let err = connect_from(&addr, IpAddr::V6(Ipv6Addr::LOCALHOST))
+                .await
+                .unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::AddrNotAvailable);
+        });
+    }
+}
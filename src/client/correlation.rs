@@ -0,0 +1,65 @@
+use std::collections::{HashMap, VecDeque};
+
+const MAX_TRACKED_REQUESTS: usize = 1000;
+
+#[derive(Debug, Default)]
+pub(crate) struct CorrelationTracker {
+    next_request: u64,
+    requests: HashMap<Vec<u8>, u64>,
+    order: VecDeque<Vec<u8>>,
+}
+
+impl CorrelationTracker {
+    pub(crate) fn record(&mut self, correlation_data: &[u8]) -> u64 {
+        self.next_request += 1;
+        let request = self.next_request;
+
+        if self
+            .requests
+            .insert(correlation_data.to_vec(), request)
+            .is_none()
+        {
+            self.order.push_back(correlation_data.to_vec());
+        }
+
+        while self.order.len() > MAX_TRACKED_REQUESTS {
+            if let Some(oldest) = self.order.pop_front() {
+                self.requests.remove(&oldest);
+            }
+        }
+
+        request
+    }
+
+    pub(crate) fn lookup(&self, correlation_data: &[u8]) -> Option<u64> {
+        self.requests.get(correlation_data).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CorrelationTracker, MAX_TRACKED_REQUESTS};
+
+    #[test]
+    fn responses_resolve_to_latest_request_number() {
+        let mut tracker = CorrelationTracker::default();
+        assert_eq!(tracker.record(b"a"), 1);
+        assert_eq!(tracker.record(b"b"), 2);
+        assert_eq!(tracker.record(b"a"), 3);
+
+        assert_eq!(tracker.lookup(b"a"), Some(3));
+        assert_eq!(tracker.lookup(b"b"), Some(2));
+        assert_eq!(tracker.lookup(b"c"), None);
+    }
+
+    #[test]
+    fn oldest_requests_are_forgotten() {
+        let mut tracker = CorrelationTracker::default();
+        for i in 0..=MAX_TRACKED_REQUESTS {
+            tracker.record(i.to_string().as_bytes());
+        }
+
+        assert_eq!(tracker.lookup(b"0"), None);
+        assert!(tracker.lookup(b"1").is_some());
+    }
+}
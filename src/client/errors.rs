@@ -0,0 +1,118 @@
+use std::io::ErrorKind;
+
+use mqtt_endpoint_tokio::mqtt_ep;
+use mqtt_endpoint_tokio::mqtt_ep::result_code::MqttError;
+
+use crate::models::error::ClientError;
+
+/// Explains an `mqtt_ep` library or protocol error, keeping its name for searching logs.
+pub(crate) fn describe_mqtt_error(err: MqttError) -> String {
+    let detail = match err {
+        MqttError::VersionMismatch | MqttError::UnsupportedProtocolVersion => {
+            "packet does not match the negotiated MQTT version"
+        }
+        MqttError::PacketTooLarge => "packet exceeds the maximum packet size",
+        MqttError::MalformedPacket => "packet is malformed",
+        MqttError::InsufficientBytes => "packet is truncated",
+        MqttError::PacketIdentifierFullyUsed => {
+            "all packet ids are in use by unacknowledged packets"
+        }
+        MqttError::PacketIdentifierConflict | MqttError::PacketIdentifierInvalid => {
+            "packet id is invalid or already in use"
+        }
+        MqttError::PacketNotAllowedToSend => "packet cannot be sent in the current session state",
+        MqttError::ReceiveMaximumExceeded => "broker's receive maximum is exceeded",
+        MqttError::ValueOutOfRange => "value is out of range for its field",
+        MqttError::InvalidQos => "QoS must be 0, 1 or 2",
+        MqttError::TopicNameInvalid => "topic name is invalid",
+        MqttError::TopicFilterInvalid => "topic filter is invalid",
+        MqttError::TopicAliasInvalid => "topic alias is invalid",
+        MqttError::ClientIdentifierNotValid => "client ID is invalid",
+        MqttError::KeepAliveTimeout => "keep alive timed out",
+        _ => return err.to_string(),
+    };
+    format!("{detail} ({err})")
+}
+
+/// Explains an endpoint `attach`/`send`/`recv` error by its underlying cause.
+pub(crate) fn describe_endpoint_error(err: &mqtt_ep::ConnectionError) -> String {
+    match err {
+        mqtt_ep::ConnectionError::Mqtt(mqtt) => describe_mqtt_error(*mqtt),
+        mqtt_ep::ConnectionError::Transport(mqtt_ep::TransportError::Io(io)) => {
+            let detail = match io.kind() {
+                ErrorKind::ConnectionReset => "connection reset",
+                ErrorKind::ConnectionAborted => "connection aborted",
+                ErrorKind::BrokenPipe => "connection closed while writing",
+                ErrorKind::UnexpectedEof => "connection closed mid-packet",
+                ErrorKind::TimedOut => "socket timed out",
+                _ => "I/O error",
+            };
+            format!("{detail}: {io}")
+        }
+        mqtt_ep::ConnectionError::Transport(mqtt_ep::TransportError::Timeout) => {
+            "transport timed out".to_string()
+        }
+        mqtt_ep::ConnectionError::Transport(mqtt_ep::TransportError::NotConnected)
+        | mqtt_ep::ConnectionError::NotConnected => "not connected to the broker".to_string(),
+        mqtt_ep::ConnectionError::ChannelClosed => "connection task has stopped".to_string(),
+        mqtt_ep::ConnectionError::AlreadyConnected => {
+            "endpoint is already attached to a connection".to_string()
+        }
+        mqtt_ep::ConnectionError::AlreadyDisconnected => {
+            "endpoint is already disconnected".to_string()
+        }
+        other => other.to_string(),
+    }
+}
+
+/// Builds the error for a failed endpoint call, classed by cause: protocol errors are not
+/// retryable, transport timeouts and I/O errors are.
+pub(crate) fn endpoint_failure(context: &str, err: &mqtt_ep::ConnectionError) -> ClientError {
+    let message = format!("{context}: {}", describe_endpoint_error(err));
+    match err {
+        mqtt_ep::ConnectionError::Mqtt(_) => ClientError::ProtocolError(message),
+        mqtt_ep::ConnectionError::Transport(mqtt_ep::TransportError::Timeout) => {
+            ClientError::Timeout(message)
+        }
+        _ => ClientError::NetworkError(message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mqtt_endpoint_tokio::mqtt_ep;
+    use mqtt_endpoint_tokio::mqtt_ep::result_code::MqttError;
+
+    use super::endpoint_failure;
+    use crate::models::error::ClientError;
+
+    #[test]
+    fn endpoint_failures_are_classed_by_cause() {
+        let err = endpoint_failure(
+            "Failed to send PUBLISH",
+            &mqtt_ep::ConnectionError::Mqtt(MqttError::PacketTooLarge),
+        );
+        assert_eq!(
+            err,
+            ClientError::ProtocolError(
+                "Failed to send PUBLISH: packet exceeds the maximum packet size (PacketTooLarge)"
+                    .to_string()
+            )
+        );
+
+        let io = std::io::Error::new(std::io::ErrorKind::ConnectionReset, "reset by peer");
+        let err = endpoint_failure(
+            "CONNECT send failed",
+            &mqtt_ep::ConnectionError::Transport(mqtt_ep::TransportError::Io(io)),
+        );
+        assert!(
+            matches!(err, ClientError::NetworkError(ref msg) if msg.contains("connection reset"))
+        );
+
+        let err = endpoint_failure(
+            "Attach failed",
+            &mqtt_ep::ConnectionError::Transport(mqtt_ep::TransportError::Timeout),
+        );
+        assert!(matches!(err, ClientError::Timeout(_)));
+    }
+}
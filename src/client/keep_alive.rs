@@ -0,0 +1,110 @@
+use std::time::{Duration, Instant};
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum KeepAliveAction {
+    Probe,
+    TimedOut,
+}
+
+#[derive(Debug)]
+pub(crate) struct KeepAliveMonitor {
+    timeout: Option<Duration>,
+    last_received: Instant,
+    last_sent: Option<Instant>,
+    probe_sent: Option<Instant>,
+}
+
+impl KeepAliveMonitor {
+    pub(crate) fn new(keep_alive_secs: u16, now: Instant) -> Self {
+        Self {
+            timeout: (keep_alive_secs > 0)
+                .then(|| Duration::from_millis(u64::from(keep_alive_secs) * 1500)),
+            last_received: now,
+            last_sent: None,
+            probe_sent: None,
+        }
+    }
+
+    pub(crate) fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        let timeout = self.timeout?;
+        Some(match self.probe_sent {
+            Some(sent_at) => sent_at + timeout / 3,
+            None => self.last_received + timeout,
+        })
+    }
+
+    pub(crate) fn received(&mut self, now: Instant) {
+        self.last_received = now;
+        self.probe_sent = None;
+    }
+
+    pub(crate) fn sent(&mut self, now: Instant) {
+        self.last_sent = Some(now);
+    }
+
+    pub(crate) fn expired(&mut self, now: Instant) -> KeepAliveAction {
+        let only_sending = self
+            .last_sent
+            .is_some_and(|sent_at| sent_at > self.last_received);
+
+        if only_sending && self.probe_sent.is_none() {
+            self.probe_sent = Some(now);
+            KeepAliveAction::Probe
+        } else {
+            KeepAliveAction::TimedOut
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{KeepAliveAction, KeepAliveMonitor};
+
+    #[test]
+    fn deadline_is_one_and_a_half_keep_alives_after_last_packet() {
+        let start = Instant::now();
+        let mut monitor = KeepAliveMonitor::new(10, start);
+        assert_eq!(monitor.deadline(), Some(start + Duration::from_secs(15)));
+
+        let later = start + Duration::from_secs(4);
+        monitor.received(later);
+        assert_eq!(monitor.deadline(), Some(later + Duration::from_secs(15)));
+    }
+
+    #[test]
+    fn zero_keep_alive_disables_monitoring() {
+        let monitor = KeepAliveMonitor::new(0, Instant::now());
+        assert_eq!(monitor.deadline(), None);
+    }
+
+    #[test]
+    fn idle_connection_times_out_without_response() {
+        let start = Instant::now();
+        let mut monitor = KeepAliveMonitor::new(10, start);
+        assert_eq!(
+            monitor.expired(start + Duration::from_secs(15)),
+            KeepAliveAction::TimedOut
+        );
+    }
+
+    #[test]
+    fn publish_only_traffic_is_probed_before_timing_out() {
+        let start = Instant::now();
+        let mut monitor = KeepAliveMonitor::new(10, start);
+        monitor.sent(start + Duration::from_secs(1));
+
+        let expiry = start + Duration::from_secs(15);
+        assert_eq!(monitor.expired(expiry), KeepAliveAction::Probe);
+        assert_eq!(monitor.deadline(), Some(expiry + Duration::from_secs(5)));
+        assert_eq!(
+            monitor.expired(expiry + Duration::from_secs(5)),
+            KeepAliveAction::TimedOut
+        );
+    }
+}
@@ -1,143 +1,73 @@
 use mqtt_endpoint_tokio::mqtt_ep;
-use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
-use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
-use rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use mqtt_endpoint_tokio::mqtt_ep::result_code::{ConnectReasonCode, DisconnectReasonCode};
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::BufReader;
+use std::io::ErrorKind;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc;
-use std::sync::{Arc, Once};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::net::TcpStream;
 use tokio::runtime::Runtime;
-use tokio::sync::{mpsc as tokio_mpsc, oneshot};
+use tokio::sync::oneshot;
 use tokio_tungstenite::client_async;
 use tokio_tungstenite::tungstenite::handshake::client::generate_key;
 use tokio_tungstenite::tungstenite::http::Request;
 
-use crate::models::client::ClientHandle;
+use crate::client::acks::{DelayedAcks, InboundAck, connection_options, send_inbound_ack};
+use crate::client::backlog::{HIDDEN_POLL_INTERVAL, HiddenBacklog};
+use crate::client::batch::PendingMessages;
+use crate::client::correlation::CorrelationTracker;
+use crate::client::errors::{describe_endpoint_error, describe_mqtt_error, endpoint_failure};
+use crate::client::keep_alive::{KeepAliveAction, KeepAliveMonitor};
+use crate::client::rtt::RttProbe;
+use crate::client::suback::match_suback;
+use crate::models::client::{ClientHandle, ShutdownReason, command_channel};
+use crate::models::error::ClientError;
 use crate::models::ipc::{ClientCommand, ClientEvent};
-use crate::models::mqtt::{MqttLoginData, TlsVerificationMode, TransportKind};
+use crate::models::mqtt::{
+    AckMode, MessageProperties, MqttLoginData, ProxyKind, ReceivedMessage, ResolvedConnection,
+    TlsSessionInfo, TransportKind,
+};
 use crate::utils::qos::qos_to_u8;
+use crate::utils::reason::{
+    describe_reason, disconnect_reason_name, reason_name, suback_reason_name,
+};
+use crate::utils::topic::topic_matches;
 
-static RUSTLS_PROVIDER_INIT: Once = Once::new();
+mod acks;
+mod backlog;
+mod batch;
+mod bind;
+mod correlation;
+mod errors;
+mod keep_alive;
+mod proxy;
+pub(crate) mod replay;
+mod rtt;
+mod suback;
+mod tls;
 
-#[derive(Debug)]
-struct InsecureServerCertVerifier;
-
-impl ServerCertVerifier for InsecureServerCertVerifier {
-    fn verify_server_cert(
-        &self,
-        _end_entity: &CertificateDer<'_>,
-        _intermediates: &[CertificateDer<'_>],
-        _server_name: &ServerName<'_>,
-        _ocsp_response: &[u8],
-        _now: UnixTime,
-    ) -> Result<ServerCertVerified, rustls::Error> {
-        Ok(ServerCertVerified::assertion())
-    }
-
-    fn verify_tls12_signature(
-        &self,
-        _message: &[u8],
-        _cert: &CertificateDer<'_>,
-        _dss: &DigitallySignedStruct,
-    ) -> Result<HandshakeSignatureValid, rustls::Error> {
-        Ok(HandshakeSignatureValid::assertion())
-    }
-
-    fn verify_tls13_signature(
-        &self,
-        _message: &[u8],
-        _cert: &CertificateDer<'_>,
-        _dss: &DigitallySignedStruct,
-    ) -> Result<HandshakeSignatureValid, rustls::Error> {
-        Ok(HandshakeSignatureValid::assertion())
-    }
+const RETAINED_ON_SUBSCRIBE_WINDOW: Duration = Duration::from_secs(5);
 
-    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
-        vec![
-            SignatureScheme::RSA_PKCS1_SHA1,
-            SignatureScheme::ECDSA_SHA1_Legacy,
-            SignatureScheme::RSA_PKCS1_SHA256,
-            SignatureScheme::ECDSA_NISTP256_SHA256,
-            SignatureScheme::RSA_PKCS1_SHA384,
-            SignatureScheme::ECDSA_NISTP384_SHA384,
-            SignatureScheme::RSA_PKCS1_SHA512,
-            SignatureScheme::ECDSA_NISTP521_SHA512,
-            SignatureScheme::RSA_PSS_SHA256,
-            SignatureScheme::RSA_PSS_SHA384,
-            SignatureScheme::RSA_PSS_SHA512,
-            SignatureScheme::ED25519,
-            SignatureScheme::ED448,
-        ]
-    }
-}
-
-fn ensure_rustls_crypto_provider() {
-    RUSTLS_PROVIDER_INIT.call_once(|| {
-        let _ = rustls::crypto::ring::default_provider().install_default();
-    });
+#[derive(Debug)]
+struct PendingPublish {
+    topic: String,
+    exactly_once: bool,
+    request: Option<u64>,
+    sent_at: Instant,
 }
 
-fn build_tls_config(
-    login: &MqttLoginData,
-    domain: &str,
-) -> Result<Option<Arc<ClientConfig>>, String> {
-    if domain.trim().is_empty() {
-        return Err("TLS transport requires a non-empty server name".to_string());
-    }
-
-    ensure_rustls_crypto_provider();
-
-    match login.tls_verification {
-        TlsVerificationMode::SystemRoots => Ok(None),
-        TlsVerificationMode::CustomCa => {
-            let path = login.tls_ca_cert_path.trim();
-            if path.is_empty() {
-                return Err("Custom CA verification requires a CA PEM file path".to_string());
-            }
-
-            let mut root_store = RootCertStore::empty();
-            let cert_result = rustls_native_certs::load_native_certs();
-            for cert in cert_result.certs {
-                let _ = root_store.add(cert);
-            }
-
-            let file = File::open(path)
-                .map_err(|err| format!("Failed to open CA PEM file '{path}': {err}"))?;
-            let mut reader = BufReader::new(file);
-            let mut found_cert = false;
-            for cert in rustls_pemfile::certs(&mut reader) {
-                let cert =
-                    cert.map_err(|err| format!("Failed to read CA PEM file '{path}': {err}"))?;
-                root_store.add(cert).map_err(|err| {
-                    format!("Failed to add certificate from CA PEM file '{path}': {err}")
-                })?;
-                found_cert = true;
-            }
-
-            if !found_cert {
-                return Err(format!(
-                    "CA PEM file '{path}' did not contain any certificates"
-                ));
-            }
-
-            Ok(Some(Arc::new(
-                ClientConfig::builder()
-                    .with_root_certificates(root_store)
-                    .with_no_client_auth(),
-            )))
-        }
-        TlsVerificationMode::InsecureSkipVerify => Ok(Some(Arc::new(
-            ClientConfig::builder()
-                .dangerous()
-                .with_custom_certificate_verifier(Arc::new(InsecureServerCertVerifier))
-                .with_no_client_auth(),
-        ))),
-    }
+struct PendingSubscribe {
+    entries: Vec<(String, u8)>,
+    sent_at: Instant,
 }
 
-fn build_websocket_request(addr: &str, path: &str) -> Result<Request<()>, String> {
-    let url = format!("ws://{addr}{path}");
+fn build_websocket_request(
+    transport: TransportKind,
+    addr: &str,
+    path: &str,
+) -> Result<Request<()>, String> {
+    let url = format!("{}://{addr}{path}", transport.scheme());
     Request::builder()
         .uri(&url)
         .header("Host", addr)
@@ -150,345 +80,937 @@ fn build_websocket_request(addr: &str, path: &str) -> Result<Request<()>, String
         .map_err(|err| format!("Failed to build WebSocket request: {err}"))
 }
 
-async fn connect_transport(
+async fn open_tcp_stream(
     login: &MqttLoginData,
-) -> Result<(Box<dyn mqtt_ep::transport::TransportOps + Send>, String), String> {
-    let resolved = login.resolve_connection()?;
+    resolved: &ResolvedConnection,
+) -> Result<TcpStream, ClientError> {
+    let proxy = login.resolve_proxy().map_err(ClientError::InvalidConfig)?;
+    let source = login
+        .resolve_source_address()
+        .map_err(ClientError::InvalidConfig)?;
+    match (proxy, source) {
+        (Some(proxy), source) => {
+            proxy::connect_through_proxy(&proxy, source, &resolved.host, resolved.port).await
+        }
+        (None, Some(source)) => bind::connect_from(&resolved.addr, source)
+            .await
+            .map_err(|err| {
+                ClientError::NetworkError(format!(
+                    "TCP connect to {} from {source} failed: {err}",
+                    resolved.addr
+                ))
+            }),
+        (None, None) => mqtt_ep::transport::connect_helper::connect_tcp(&resolved.addr, None)
+            .await
+            .map_err(|err| {
+                ClientError::NetworkError(format!("TCP connect to {} failed: {err}", resolved.addr))
+            }),
+    }
+}
+
+struct ConnectedTransport {
+    transport: Box<dyn mqtt_ep::transport::TransportOps + Send>,
+    display_label: String,
+    tls_session: Option<TlsSessionInfo>,
+}
+
+#[cfg(unix)]
+async fn connect_unix_transport(
+    resolved: ResolvedConnection,
+) -> Result<ConnectedTransport, ClientError> {
+    let path = resolved.socket_path.as_deref().ok_or_else(|| {
+        ClientError::InvalidConfig("Unix socket transport requires a socket path".to_string())
+    })?;
+    let stream = mqtt_ep::transport::connect_helper::connect_unix(path, None)
+        .await
+        .map_err(|err| {
+            ClientError::NetworkError(format!("Unix socket connect to {path} failed: {err}"))
+        })?;
+
+    Ok(ConnectedTransport {
+        transport: Box::new(mqtt_ep::transport::UnixStreamTransport::from_stream(stream)),
+        display_label: resolved.display_label,
+        tls_session: None,
+    })
+}
+
+#[cfg(not(unix))]
+async fn connect_unix_transport(
+    _resolved: ResolvedConnection,
+) -> Result<ConnectedTransport, ClientError> {
+    Err(ClientError::InvalidConfig(
+        "Unix domain sockets are not supported on this platform".to_string(),
+    ))
+}
+
+async fn connect_transport(login: &MqttLoginData) -> Result<ConnectedTransport, ClientError> {
+    let resolved = login
+        .resolve_connection()
+        .map_err(ClientError::InvalidConfig)?;
+    let mut tls_session = None;
     let transport: Box<dyn mqtt_ep::transport::TransportOps + Send> = match resolved.transport {
+        TransportKind::Unix => {
+            if login.proxy_kind != ProxyKind::None || !login.source_address.trim().is_empty() {
+                return Err(ClientError::InvalidConfig(
+                    "A proxy or source address cannot be used with the Unix socket transport"
+                        .to_string(),
+                ));
+            }
+            return connect_unix_transport(resolved).await;
+        }
         TransportKind::Tcp => {
-            let stream = mqtt_ep::transport::connect_helper::connect_tcp(&resolved.addr, None)
-                .await
-                .map_err(|err| format!("TCP connect failed: {err}"))?;
+            let stream = open_tcp_stream(login, &resolved).await?;
             Box::new(mqtt_ep::transport::TcpTransport::from_stream(stream))
         }
         TransportKind::Tls => {
-            let domain = resolved
-                .tls_domain
-                .as_deref()
-                .ok_or_else(|| "TLS transport requires a server name".to_string())?;
-            let tls_config = build_tls_config(login, domain)?;
-            let stream = mqtt_ep::transport::connect_helper::connect_tcp_tls(
-                &resolved.addr,
-                domain,
-                tls_config,
-                None,
-            )
-            .await
-            .map_err(|err| format!("TLS connect failed: {err}"))?;
+            let stream = open_tcp_stream(login, &resolved).await?;
+            let domain = resolved.tls_domain.as_deref().ok_or_else(|| {
+                ClientError::InvalidConfig("TLS transport requires a server name".to_string())
+            })?;
+            let stream = tls::tls_handshake(login, stream, domain).await?;
+            tls_session = Some(tls::session_info(&stream));
             Box::new(mqtt_ep::transport::TlsTransport::from_stream(stream))
         }
         TransportKind::Ws => {
-            let path = resolved
-                .ws_path
-                .as_deref()
-                .ok_or_else(|| "WebSocket transport requires a path".to_string())?;
-            let tcp_stream = mqtt_ep::transport::connect_helper::connect_tcp(&resolved.addr, None)
-                .await
-                .map_err(|err| format!("WebSocket TCP connect failed: {err}"))?;
-            let request = build_websocket_request(&resolved.addr, path)?;
-            let (stream, _response) = client_async(request, tcp_stream)
-                .await
-                .map_err(|err| format!("WebSocket connect failed: {err}"))?;
+            let stream = open_tcp_stream(login, &resolved).await?;
+            let path = resolved.ws_path.as_deref().ok_or_else(|| {
+                ClientError::InvalidConfig("WebSocket transport requires a path".to_string())
+            })?;
+            let request = build_websocket_request(resolved.transport, &resolved.addr, path)
+                .map_err(ClientError::ProtocolError)?;
+            let (stream, _response) = client_async(request, stream).await.map_err(|err| {
+                ClientError::NetworkError(format!("WebSocket connect failed: {err}"))
+            })?;
             Box::new(mqtt_ep::transport::WebSocketTransport::from_tcp_client_stream(stream))
         }
         TransportKind::Wss => {
-            let domain = resolved
-                .tls_domain
-                .as_deref()
-                .ok_or_else(|| "Secure WebSocket transport requires a server name".to_string())?;
-            let path = resolved
-                .ws_path
-                .as_deref()
-                .ok_or_else(|| "Secure WebSocket transport requires a path".to_string())?;
-            let tls_config = build_tls_config(login, domain)?;
-            let stream = mqtt_ep::transport::connect_helper::connect_tcp_tls_ws(
-                &resolved.addr,
-                domain,
-                path,
-                tls_config,
-                None,
-                None,
-            )
-            .await
-            .map_err(|err| format!("Secure WebSocket connect failed: {err}"))?;
+            let stream = open_tcp_stream(login, &resolved).await?;
+            let domain = resolved.tls_domain.as_deref().ok_or_else(|| {
+                ClientError::InvalidConfig(
+                    "Secure WebSocket transport requires a server name".to_string(),
+                )
+            })?;
+            let path = resolved.ws_path.as_deref().ok_or_else(|| {
+                ClientError::InvalidConfig("Secure WebSocket transport requires a path".to_string())
+            })?;
+            let stream = tls::tls_handshake(login, stream, domain).await?;
+            tls_session = Some(tls::session_info(&stream));
+            let request = build_websocket_request(resolved.transport, &resolved.addr, path)
+                .map_err(ClientError::ProtocolError)?;
+            let (stream, _response) = client_async(request, stream).await.map_err(|err| {
+                ClientError::NetworkError(format!("Secure WebSocket connect failed: {err}"))
+            })?;
             Box::new(mqtt_ep::transport::WebSocketTransport::from_tls_client_stream(stream))
         }
     };
 
-    Ok((transport, resolved.display_label))
+    Ok(ConnectedTransport {
+        transport,
+        display_label: resolved.display_label,
+        tls_session,
+    })
+}
+
+fn build_publish_properties(
+    properties: &MessageProperties,
+) -> Result<mqtt_ep::packet::Properties, String> {
+    let mut props = mqtt_ep::packet::Properties::new();
+
+    if let Some(secs) = properties.message_expiry_secs {
+        let expiry = mqtt_ep::packet::MessageExpiryInterval::new(secs)
+            .map_err(|err| format!("Invalid message expiry interval {secs}: {err}"))?;
+        props.push(expiry.into());
+    }
+
+    if let Some(content_type) = properties.content_type.as_deref() {
+        let content_type = mqtt_ep::packet::ContentType::new(content_type)
+            .map_err(|err| format!("Invalid content type '{content_type}': {err}"))?;
+        props.push(content_type.into());
+    }
+
+    if let Some(response_topic) = properties.response_topic.as_deref() {
+        let response_topic = mqtt_ep::packet::ResponseTopic::new(response_topic)
+            .map_err(|err| format!("Invalid response topic '{response_topic}': {err}"))?;
+        props.push(response_topic.into());
+    }
+
+    if let Some(correlation_data) = properties.correlation_data.as_deref() {
+        let correlation_data = mqtt_ep::packet::CorrelationData::new(correlation_data.to_vec())
+            .map_err(|err| format!("Invalid correlation data: {err}"))?;
+        props.push(correlation_data.into());
+    }
+
+    if properties.payload_is_utf8 {
+        let indicator =
+            mqtt_ep::packet::PayloadFormatIndicator::new(mqtt_ep::packet::PayloadFormat::String)
+                .map_err(|err| format!("Invalid payload format indicator: {err}"))?;
+        props.push(indicator.into());
+    }
+
+    for (key, value) in &properties.user_properties {
+        let user_property = mqtt_ep::packet::UserProperty::new(key, value)
+            .map_err(|err| format!("Invalid user property '{key}': {err}"))?;
+        props.push(user_property.into());
+    }
+
+    Ok(props)
+}
+
+fn message_properties_from(props: &mqtt_ep::packet::Properties) -> MessageProperties {
+    let mut properties = MessageProperties::default();
+
+    for prop in props {
+        match prop {
+            mqtt_ep::packet::Property::MessageExpiryInterval(expiry) => {
+                properties.message_expiry_secs = Some(expiry.val());
+            }
+            mqtt_ep::packet::Property::ContentType(content_type) => {
+                properties.content_type = Some(content_type.val().to_string());
+            }
+            mqtt_ep::packet::Property::ResponseTopic(response_topic) => {
+                properties.response_topic = Some(response_topic.val().to_string());
+            }
+            mqtt_ep::packet::Property::CorrelationData(correlation_data) => {
+                properties.correlation_data = Some(correlation_data.val().to_vec());
+            }
+            mqtt_ep::packet::Property::PayloadFormatIndicator(indicator) => {
+                properties.payload_is_utf8 =
+                    indicator.val() == mqtt_ep::packet::PayloadFormat::String as u8;
+            }
+            mqtt_ep::packet::Property::UserProperty(user_property) => {
+                properties.user_properties.push((
+                    user_property.key().to_string(),
+                    user_property.val().to_string(),
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    properties
+}
+
+fn connack_failure(
+    reason_code: ConnectReasonCode,
+    props: &mqtt_ep::packet::Properties,
+) -> Option<ClientError> {
+    if reason_code == ConnectReasonCode::Success {
+        return None;
+    }
+
+    let code = reason_code as u8;
+    let mut message = format!("Broker rejected CONNECT: {}", reason_name(code));
+    let mut user_properties = Vec::new();
+    for prop in props {
+        match prop {
+            mqtt_ep::packet::Property::ReasonString(reason) => {
+                message.push_str(&format!(", reason: {}", reason.val()));
+            }
+            mqtt_ep::packet::Property::UserProperty(user_property) => {
+                user_properties.push(format!("{}={}", user_property.key(), user_property.val()));
+            }
+            _ => {}
+        }
+    }
+    if !user_properties.is_empty() {
+        message.push_str(&format!(
+            ", user properties: {}",
+            user_properties.join(", ")
+        ));
+    }
+
+    Some(match reason_code {
+        ConnectReasonCode::BadUserNameOrPassword
+        | ConnectReasonCode::NotAuthorized
+        | ConnectReasonCode::BadAuthenticationMethod
+        | ConnectReasonCode::Banned => ClientError::AuthFailed(format!(
+            "Authentication failed: {message} (reason code {code:#04x})"
+        )),
+        ConnectReasonCode::ServerUnavailable
+        | ConnectReasonCode::ServerBusy
+        | ConnectReasonCode::QuotaExceeded
+        | ConnectReasonCode::ConnectionRateExceeded => ClientError::BrokerDisconnect {
+            code,
+            reason: message,
+        },
+        ConnectReasonCode::ClientIdentifierNotValid => {
+            ClientError::InvalidConfig(format!("{message} (reason code {code:#04x})"))
+        }
+        _ => ClientError::ProtocolError(format!("{message} (reason code {code:#04x})")),
+    })
+}
+
+fn receive_failure(err: &mqtt_ep::ConnectionError) -> ClientError {
+    let closed_by_peer = match err {
+        mqtt_ep::ConnectionError::NotConnected
+        | mqtt_ep::ConnectionError::ChannelClosed
+        | mqtt_ep::ConnectionError::Transport(mqtt_ep::TransportError::NotConnected) => true,
+        mqtt_ep::ConnectionError::Transport(mqtt_ep::TransportError::Io(io)) => matches!(
+            io.kind(),
+            ErrorKind::UnexpectedEof
+                | ErrorKind::ConnectionReset
+                | ErrorKind::ConnectionAborted
+                | ErrorKind::BrokenPipe
+        ),
+        _ => false,
+    };
+
+    match err {
+        _ if closed_by_peer => ClientError::NetworkError(
+            "Connection closed by broker (no DISCONNECT received)".to_string(),
+        ),
+        mqtt_ep::ConnectionError::Transport(mqtt_ep::TransportError::Timeout) => {
+            ClientError::Timeout("Timed out waiting for the broker".to_string())
+        }
+        mqtt_ep::ConnectionError::Mqtt(_) => {
+            ClientError::ProtocolError(format!("Malformed packet from broker: {err}"))
+        }
+        _ => ClientError::NetworkError(format!("Receive loop failed: {err}")),
+    }
+}
+
+/// Sends DISCONNECT. A normal disconnect makes the broker discard the Last Will; with
+/// `with_will` the reason code asks it to publish the will anyway.
+async fn send_disconnect(
+    endpoint: &mqtt_ep::endpoint::Endpoint<mqtt_ep::role::Client>,
+    with_will: bool,
+    event_tx: &mpsc::Sender<ClientEvent>,
+) {
+    let mut builder = mqtt_ep::packet::v5_0::Disconnect::builder();
+    if with_will {
+        builder = builder.reason_code(DisconnectReasonCode::DisconnectWithWillMessage);
+    }
+    if let Ok(packet) = builder.build()
+        && endpoint.send(packet).await.is_ok()
+    {
+        let summary = if with_will {
+            "DISCONNECT reason=DisconnectWithWillMessage"
+        } else {
+            "DISCONNECT"
+        };
+        let _ = event_tx.send(ClientEvent::PacketSent(summary.to_string()));
+    }
+}
+
+/// Sends one SUBSCRIBE covering every `(topic, qos)` entry, in order.
+async fn send_subscribe(
+    endpoint: &mqtt_ep::endpoint::Endpoint<mqtt_ep::role::Client>,
+    entries: &[(String, u8)],
+    event_tx: &mpsc::Sender<ClientEvent>,
+) -> Option<u16> {
+    let mut sub_entries = Vec::with_capacity(entries.len());
+    for (topic, qos) in entries {
+        let qos_level = match mqtt_ep::packet::Qos::try_from(*qos) {
+            Ok(level) => level,
+            Err(err) => {
+                let _ = event_tx.send(ClientEvent::Error(ClientError::ProtocolError(format!(
+                    "Invalid subscribe QoS {qos}: {err}"
+                ))));
+                return None;
+            }
+        };
+
+        let sub_opts = mqtt_ep::packet::SubOpts::new().set_qos(qos_level);
+        match mqtt_ep::packet::SubEntry::new(topic, sub_opts) {
+            Ok(entry) => sub_entries.push(entry),
+            Err(err) => {
+                let _ = event_tx.send(ClientEvent::Error(ClientError::ProtocolError(format!(
+                    "Invalid subscription topic '{topic}': {}",
+                    describe_mqtt_error(err)
+                ))));
+                return None;
+            }
+        }
+    }
+
+    let packet_id = match endpoint.acquire_packet_id().await {
+        Ok(id) => id,
+        Err(err) => {
+            let _ = event_tx.send(ClientEvent::Error(ClientError::ProtocolError(format!(
+                "Failed to acquire packet id: {}",
+                describe_endpoint_error(&err)
+            ))));
+            return None;
+        }
+    };
+
+    let subscribe_packet = match mqtt_ep::packet::v5_0::Subscribe::builder()
+        .packet_id(packet_id)
+        .entries(sub_entries)
+        .build()
+    {
+        Ok(packet) => packet,
+        Err(err) => {
+            let _ = event_tx.send(ClientEvent::Error(ClientError::ProtocolError(format!(
+                "Failed to build SUBSCRIBE: {}",
+                describe_mqtt_error(err)
+            ))));
+            return None;
+        }
+    };
+
+    if let Err(err) = endpoint.send(subscribe_packet).await {
+        let _ = event_tx.send(ClientEvent::Error(endpoint_failure(
+            "Failed to send SUBSCRIBE",
+            &err,
+        )));
+        return None;
+    }
+    let filters = entries
+        .iter()
+        .map(|(topic, qos)| format!("topic={topic} qos={qos}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let _ = event_tx.send(ClientEvent::PacketSent(format!(
+        "SUBSCRIBE id={packet_id} {filters}"
+    )));
+
+    Some(packet_id)
+}
+
+async fn send_unsubscribe(
+    endpoint: &mqtt_ep::endpoint::Endpoint<mqtt_ep::role::Client>,
+    topic: &str,
+    event_tx: &mpsc::Sender<ClientEvent>,
+) -> Option<u16> {
+    let packet_id = match endpoint.acquire_packet_id().await {
+        Ok(id) => id,
+        Err(err) => {
+            let _ = event_tx.send(ClientEvent::Error(ClientError::ProtocolError(format!(
+                "Failed to acquire packet id: {}",
+                describe_endpoint_error(&err)
+            ))));
+            return None;
+        }
+    };
+
+    let unsubscribe_packet = match mqtt_ep::packet::v5_0::Unsubscribe::builder()
+        .packet_id(packet_id)
+        .entries(vec![topic])
+        .and_then(|builder| builder.build())
+    {
+        Ok(packet) => packet,
+        Err(err) => {
+            let _ = event_tx.send(ClientEvent::Error(ClientError::ProtocolError(format!(
+                "Failed to build UNSUBSCRIBE: {}",
+                describe_mqtt_error(err)
+            ))));
+            return None;
+        }
+    };
+
+    if let Err(err) = endpoint.send(unsubscribe_packet).await {
+        let _ = event_tx.send(ClientEvent::Error(endpoint_failure(
+            "Failed to send UNSUBSCRIBE",
+            &err,
+        )));
+        return None;
+    }
+    let _ = event_tx.send(ClientEvent::PacketSent(format!(
+        "UNSUBSCRIBE id={packet_id} topic={topic}"
+    )));
+
+    Some(packet_id)
 }
 
-pub(crate) fn spawn_client(runtime: &Runtime, tab_id: u64, login: MqttLoginData) -> ClientHandle {
+/// Sends the next RTT ping, or reports the summary and drops the probe subscription once done.
+async fn advance_rtt_probe(
+    endpoint: &mqtt_ep::endpoint::Endpoint<mqtt_ep::role::Client>,
+    rtt_probe: &mut Option<RttProbe>,
+    pending_unsubscribe: &mut HashMap<u16, String>,
+    event_tx: &mpsc::Sender<ClientEvent>,
+) {
+    let Some(probe) = rtt_probe.as_mut() else {
+        return;
+    };
+    if let Some(payload) = probe.next_ping(Instant::now()) {
+        rtt::send_ping(endpoint, &probe.topic, payload, event_tx).await;
+        return;
+    }
+    if !probe.is_finished() {
+        return;
+    }
+
+    let _ = event_tx.send(ClientEvent::RttFinished(probe.summary()));
+    let topic = std::mem::take(&mut probe.topic);
+    *rtt_probe = None;
+    if let Some(packet_id) = send_unsubscribe(endpoint, &topic, event_tx).await {
+        pending_unsubscribe.insert(packet_id, topic);
+    }
+}
+
+pub(crate) fn spawn_client(
+    runtime: &Runtime,
+    tab_id: u64,
+    login: MqttLoginData,
+    command_limit: Option<usize>,
+) -> ClientHandle {
     let (event_tx, event_rx) = mpsc::channel();
-    let (command_tx, mut command_rx) = tokio_mpsc::unbounded_channel::<ClientCommand>();
-    let (shutdown_tx, mut shutdown_rx) = oneshot::channel::<()>();
+    let (command_tx, mut command_rx) = command_channel(command_limit);
+    let (shutdown_tx, mut shutdown_rx) = oneshot::channel::<ShutdownReason>();
     let client_id = login.effective_client_id(tab_id);
     let keep_alive_secs = login.effective_keep_alive_secs();
+    let ui_hidden = Arc::new(AtomicBool::new(false));
+    let task_ui_hidden = Arc::clone(&ui_hidden);
 
     let join_handle = runtime.spawn(async move {
         let resolved = match login.resolve_connection() {
             Ok(resolved) => resolved,
             Err(err) => {
-                let _ = event_tx.send(ClientEvent::Disconnected(format!(
-                    "Invalid connection settings: {err}"
+                let _ = event_tx.send(ClientEvent::Disconnected(ClientError::InvalidConfig(
+                    format!("Invalid connection settings: {err}"),
                 )));
                 return;
             }
         };
-        let _ = event_tx.send(ClientEvent::Status(format!(
-            "Connecting via {} to {}",
-            resolved.transport.label(),
-            resolved.display_label
-        )));
-
-        let endpoint = mqtt_ep::endpoint::Endpoint::<mqtt_ep::role::Client>::new(mqtt_ep::Version::V5_0);
-        let (transport, display_label) = match connect_transport(&login).await {
-            Ok(transport) => transport,
+        let proxy = match login.resolve_proxy() {
+            Ok(proxy) => proxy,
             Err(err) => {
-                let _ = event_tx.send(ClientEvent::Disconnected(err));
+                let _ = event_tx.send(ClientEvent::Disconnected(ClientError::InvalidConfig(
+                    format!("Invalid proxy settings: {err}"),
+                )));
                 return;
             }
         };
-        if let Err(err) = endpoint
-            .attach(transport, mqtt_ep::endpoint::Mode::Client)
-            .await
-        {
-            let _ = event_tx.send(ClientEvent::Disconnected(format!("Attach failed: {err}")));
-            return;
-        }
+        let _ = event_tx.send(ClientEvent::Status(match proxy {
+            Some(proxy) => format!(
+                "Connecting via {} to {} through {} proxy {}",
+                resolved.transport.label(),
+                resolved.display_label,
+                proxy.kind.label(),
+                proxy.addr
+            ),
+            None => format!(
+                "Connecting via {} to {}",
+                resolved.transport.label(),
+                resolved.display_label
+            ),
+        }));
 
-        let mut connect_builder = match mqtt_ep::packet::v5_0::Connect::builder().client_id(&client_id) {
-            Ok(builder) => builder.keep_alive(keep_alive_secs).clean_start(true),
-            Err(err) => {
-                let _ = event_tx.send(ClientEvent::Disconnected(format!("Client ID setup failed: {err}")));
-                let _ = endpoint.close().await;
-                return;
+        let endpoint = mqtt_ep::endpoint::Endpoint::<mqtt_ep::role::Client>::new(mqtt_ep::Version::V5_0);
+        // Races the whole connect phase against shutdown, so closing or reconnecting a tab
+        // stuck on an unresponsive broker or proxy ends the attempt.
+        let connect_phase = async {
+            let ConnectedTransport {
+                transport,
+                display_label,
+                tls_session,
+            } = match connect_transport(&login).await {
+                Ok(connected) => connected,
+                Err(err) => {
+                    let _ = event_tx.send(ClientEvent::Disconnected(err));
+                    return None;
+                }
+            };
+            if let Err(err) = endpoint
+                .attach_with_options(transport, mqtt_ep::endpoint::Mode::Client, connection_options())
+                .await
+            {
+                let _ = event_tx.send(ClientEvent::Disconnected(endpoint_failure("Attach failed", &err)));
+                return None;
             }
-        };
 
-        if let Some(username) = login.username_opt() {
-            connect_builder = match connect_builder.user_name(username) {
-                Ok(builder) => builder,
+            let mut connect_builder = match mqtt_ep::packet::v5_0::Connect::builder().client_id(&client_id) {
+                Ok(builder) => builder.keep_alive(keep_alive_secs).clean_start(true),
                 Err(err) => {
-                    let _ = event_tx.send(ClientEvent::Disconnected(format!("Username setup failed: {err}")));
+                    let _ = event_tx.send(ClientEvent::Disconnected(ClientError::InvalidConfig(format!("Client ID setup failed: {}", describe_mqtt_error(err)))));
                     let _ = endpoint.close().await;
-                    return;
+                    return None;
                 }
             };
 
-            if let Some(password) = login.password_opt() {
-                connect_builder = match connect_builder.password(password.as_bytes().to_vec()) {
+            if let Some(username) = login.username_opt() {
+                connect_builder = match connect_builder.user_name(username) {
                     Ok(builder) => builder,
                     Err(err) => {
-                        let _ = event_tx.send(ClientEvent::Disconnected(format!("Password setup failed: {err}")));
+                        let _ = event_tx.send(ClientEvent::Disconnected(ClientError::InvalidConfig(format!("Username setup failed: {}", describe_mqtt_error(err)))));
                         let _ = endpoint.close().await;
-                        return;
+                        return None;
                     }
                 };
+
+                if let Some(password) = login.password_opt() {
+                    connect_builder = match connect_builder.password(password.as_bytes().to_vec()) {
+                        Ok(builder) => builder,
+                        Err(err) => {
+                            let _ = event_tx.send(ClientEvent::Disconnected(ClientError::InvalidConfig(format!("Password setup failed: {}", describe_mqtt_error(err)))));
+                            let _ = endpoint.close().await;
+                            return None;
+                        }
+                    };
+                }
             }
-        }
 
-        if let Some(testament) = login.testament_and_last_will_opt() {
-            let will_topic = login
-                .testament_topic_opt()
-                .map(str::to_owned)
-                .unwrap_or_else(|| format!("mqui/{client_id}/last-will"));
-            let will_qos = match mqtt_ep::packet::Qos::try_from(login.testament_qos) {
-                Ok(qos) => qos,
-                Err(_) => mqtt_ep::packet::Qos::AtMostOnce,
-            };
-            connect_builder = match connect_builder.will_message(
-                &will_topic,
-                testament.as_bytes().to_vec(),
-                will_qos,
-                login.testament_retain,
-            ) {
-                Ok(builder) => builder,
+            if let Some(testament) = login.testament_and_last_will_opt() {
+                let will_topic = login
+                    .testament_topic_opt()
+                    .map(str::to_owned)
+                    .unwrap_or_else(|| {
+                        if client_id.is_empty() {
+                            "mqui/last-will".to_string()
+                        } else {
+                            format!("mqui/{client_id}/last-will")
+                        }
+                    });
+                let will_qos = match mqtt_ep::packet::Qos::try_from(login.testament_qos) {
+                    Ok(qos) => qos,
+                    Err(_) => mqtt_ep::packet::Qos::AtMostOnce,
+                };
+                connect_builder = match connect_builder.will_message(
+                    &will_topic,
+                    testament.as_bytes().to_vec(),
+                    will_qos,
+                    login.testament_retain,
+                ) {
+                    Ok(builder) => builder,
+                    Err(err) => {
+                        let _ = event_tx.send(ClientEvent::Disconnected(ClientError::InvalidConfig(
+                            format!("Last Will setup failed: {}", describe_mqtt_error(err)),
+                        )));
+                        let _ = endpoint.close().await;
+                        return None;
+                    }
+                };
+            }
+
+            let mut connect_summary = format!("CONNECT client_id={client_id:?} keep_alive={keep_alive_secs}s clean_start=true");
+            if let Some(username) = login.username_opt() {
+                connect_summary.push_str(&format!(" username={username:?}"));
+            }
+            if login.testament_and_last_will_opt().is_some() {
+                connect_summary.push_str(" will=yes");
+            }
+
+            let connect_packet = match connect_builder.build() {
+                Ok(packet) => packet,
                 Err(err) => {
-                    let _ = event_tx.send(ClientEvent::Disconnected(format!(
-                        "Last Will setup failed: {err}"
-                    )));
+                    let _ = event_tx.send(ClientEvent::Disconnected(ClientError::InvalidConfig(format!("CONNECT build failed: {}", describe_mqtt_error(err)))));
                     let _ = endpoint.close().await;
-                    return;
+                    return None;
                 }
             };
-        }
 
-        let connect_packet = match connect_builder.build() {
-            Ok(packet) => packet,
-            Err(err) => {
-                let _ = event_tx.send(ClientEvent::Disconnected(format!("CONNECT build failed: {err}")));
+            if let Err(err) = endpoint.send(connect_packet).await {
+                let _ = event_tx.send(ClientEvent::Disconnected(endpoint_failure("CONNECT send failed", &err)));
                 let _ = endpoint.close().await;
-                return;
+                return None;
             }
-        };
+            let _ = event_tx.send(ClientEvent::PacketSent(connect_summary));
 
-        if let Err(err) = endpoint.send(connect_packet).await {
-            let _ = event_tx.send(ClientEvent::Disconnected(format!("CONNECT send failed: {err}")));
-            let _ = endpoint.close().await;
-            return;
-        }
+            let connack = match endpoint.recv().await {
+                Ok(packet) => packet,
+                Err(err) => {
+                    let hint = if resolved.transport.uses_tls() && login.has_tls_client_identity() {
+                        " (the broker may have rejected the client certificate)"
+                    } else {
+                        ""
+                    };
+                    let _ = event_tx.send(ClientEvent::Disconnected(endpoint_failure(&format!("CONNACK recv failed{hint}"), &err)));
+                    let _ = endpoint.close().await;
+                    return None;
+                }
+            };
 
-        let connack = match endpoint.recv().await {
-            Ok(packet) => packet,
-            Err(err) => {
-                let _ = event_tx.send(ClientEvent::Disconnected(format!("CONNACK recv failed: {err}")));
+            let effective_keep_alive = match connack {
+                mqtt_ep::packet::Packet::V5_0Connack(connack) => {
+                    if let Some(err) = connack_failure(connack.reason_code(), connack.props()) {
+                        let _ = event_tx.send(ClientEvent::Disconnected(err));
+                        let _ = endpoint.close().await;
+                        return None;
+                    }
+                    let server_keep_alive = connack.props().iter().find_map(|prop| match prop {
+                        mqtt_ep::packet::Property::ServerKeepAlive(value) => Some(value.val()),
+                        _ => None,
+                    });
+                    let effective_keep_alive = server_keep_alive.unwrap_or(keep_alive_secs);
+                    let _ = event_tx.send(ClientEvent::Connected);
+                    let _ = event_tx.send(ClientEvent::KeepAlive {
+                        secs: effective_keep_alive,
+                        from_server: server_keep_alive.is_some(),
+                    });
+                    let assigned_client_id = connack.props().iter().find_map(|prop| match prop {
+                        mqtt_ep::packet::Property::AssignedClientIdentifier(value) => Some(value.val().to_string()),
+                        _ => None,
+                    });
+                    if let Some(assigned_client_id) = assigned_client_id {
+                        let _ = event_tx.send(ClientEvent::AssignedClientId(assigned_client_id));
+                    }
+                    let maximum_qos = connack.props().iter().find_map(|prop| match prop {
+                        mqtt_ep::packet::Property::MaximumQos(value) => Some(value.val()),
+                        _ => None,
+                    });
+                    if let Some(maximum_qos) = maximum_qos {
+                        let _ = event_tx.send(ClientEvent::MaximumQos(maximum_qos));
+                    }
+                    if let Some(tls_session) = tls_session {
+                        let _ = event_tx.send(ClientEvent::TlsSession(tls_session));
+                    }
+                    let _ = event_tx.send(ClientEvent::Status(format!("Connected to {display_label}")));
+                    effective_keep_alive
+                }
+                other => {
+                    let _ = event_tx.send(ClientEvent::Disconnected(ClientError::ProtocolError(
+                        format!("Expected CONNACK, got {:?}", other.packet_type()),
+                    )));
+                    let _ = endpoint.close().await;
+                    return None;
+                }
+            };
+            Some(effective_keep_alive)
+        };
+        let effective_keep_alive = tokio::select! {
+            effective_keep_alive = connect_phase => effective_keep_alive,
+            _ = &mut shutdown_rx => {
                 let _ = endpoint.close().await;
-                return;
+                let _ = event_tx.send(ClientEvent::Status("Closed".to_string()));
+                None
             }
         };
+        let Some(effective_keep_alive) = effective_keep_alive else {
+            return;
+        };
 
-        match connack {
-            mqtt_ep::packet::Packet::V5_0Connack(_) => {
-                let _ = event_tx.send(ClientEvent::Connected);
-                let _ = event_tx.send(ClientEvent::Status(format!("Connected to {display_label}")));
-            }
-            other => {
-                let _ = event_tx.send(ClientEvent::Disconnected(format!(
-                    "Expected CONNACK, got {:?}",
-                    other.packet_type()
-                )));
-                let _ = endpoint.close().await;
-                return;
+        let mut pending_subscribe: HashMap<u16, PendingSubscribe> = HashMap::new();
+        let mut pending_unsubscribe: HashMap<u16, String> = HashMap::new();
+        let mut pending_publish: HashMap<u16, PendingPublish> = HashMap::new();
+        let mut correlations = CorrelationTracker::default();
+        let mut recent_subscriptions: Vec<(String, Instant)> = Vec::new();
+        let mut keep_alive = KeepAliveMonitor::new(effective_keep_alive, Instant::now());
+        let mut ack_mode = AckMode::Auto;
+        let mut ack_delay = Duration::ZERO;
+        let mut delayed_acks = DelayedAcks::default();
+        let mut pending_messages = PendingMessages::default();
+        let mut hidden_backlog = HiddenBacklog::default();
+        let ui_hidden = task_ui_hidden;
+        let mut manual_acks: HashMap<u16, InboundAck> = HashMap::new();
+        let mut rtt_probe: Option<RttProbe> = None;
+
+        let auto_subscribe: Vec<(String, u8)> = login
+            .auto_subscribe
+            .iter()
+            .filter(|(topic, _)| !topic.trim().is_empty())
+            .cloned()
+            .collect();
+        if !auto_subscribe.is_empty()
+            && let Some(packet_id) = send_subscribe(&endpoint, &auto_subscribe, &event_tx).await
+        {
+            for (topic, qos) in &auto_subscribe {
+                let _ = event_tx.send(ClientEvent::Log(format!("Auto-subscribing to '{topic}' (QoS {qos})")));
+                recent_subscriptions.push((topic.clone(), Instant::now()));
             }
+            pending_subscribe.insert(packet_id, PendingSubscribe { entries: auto_subscribe, sent_at: Instant::now() });
         }
 
-        let mut pending_subscribe: HashMap<u16, (String, u8)> = HashMap::new();
-        let mut pending_unsubscribe: HashMap<u16, String> = HashMap::new();
-        let mut pending_publish: HashMap<u16, (String, bool)> = HashMap::new();
-
         loop {
+            let keep_alive_deadline = keep_alive.deadline();
+            let delayed_ack_deadline = delayed_acks.deadline();
+            let rtt_deadline = rtt_probe.as_ref().and_then(RttProbe::deadline);
+            let batch_deadline = pending_messages.deadline();
+            let backlog_waiting = !hidden_backlog.is_empty();
             tokio::select! {
-                _ = &mut shutdown_rx => {
+                reason = &mut shutdown_rx => {
+                    let with_will = login.testament_on_quit && reason.is_ok_and(ShutdownReason::sends_will);
+                    send_disconnect(&endpoint, with_will, &event_tx).await;
                     let _ = endpoint.close().await;
                     let _ = event_tx.send(ClientEvent::Status("Closed".to_string()));
                     break;
                 }
+                _ = async {
+                    match keep_alive_deadline {
+                        Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+                        None => std::future::pending().await,
+                    }
+                } => {
+                    match keep_alive.expired(Instant::now()) {
+                        KeepAliveAction::Probe => {
+                            let _ = event_tx.send(ClientEvent::Log("No packets received recently, sending PINGREQ".to_string()));
+                            if let Err(err) = endpoint.send(mqtt_ep::packet::v5_0::Pingreq::new()).await {
+                                let _ = event_tx.send(ClientEvent::Error(endpoint_failure("Failed to send PINGREQ", &err)));
+                            } else {
+                                let _ = event_tx.send(ClientEvent::PacketSent("PINGREQ".to_string()));
+                            }
+                        }
+                        KeepAliveAction::TimedOut => {
+                            let waited = keep_alive.timeout().unwrap_or_default().as_secs();
+                            let _ = event_tx.send(ClientEvent::Disconnected(ClientError::Timeout(format!(
+                                "Keep-alive timeout: no packet received from broker for {waited}s"
+                            ))));
+                            let _ = endpoint.close().await;
+                            break;
+                        }
+                    }
+                }
+                _ = async {
+                    match batch_deadline {
+                        Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+                        None => std::future::pending().await,
+                    }
+                } => {
+                    pending_messages.flush(&event_tx);
+                }
+                _ = async {
+                    if backlog_waiting {
+                        tokio::time::sleep(HIDDEN_POLL_INTERVAL).await;
+                    } else {
+                        std::future::pending::<()>().await;
+                    }
+                } => {
+                    if !ui_hidden.load(Ordering::Relaxed) {
+                        hidden_backlog.flush(&event_tx);
+                    }
+                }
+                _ = async {
+                    match delayed_ack_deadline {
+                        Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+                        None => std::future::pending().await,
+                    }
+                } => {
+                    for ack in delayed_acks.take_due(Instant::now()) {
+                        send_inbound_ack(&endpoint, ack, &event_tx).await;
+                    }
+                }
+                _ = async {
+                    match rtt_deadline {
+                        Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+                        None => std::future::pending().await,
+                    }
+                } => {
+                    if let Some(probe) = rtt_probe.as_mut() {
+                        let subscribing = probe.is_subscribing();
+                        if !probe.timed_out(Instant::now()) {
+                            continue;
+                        }
+                        let _ = event_tx.send(ClientEvent::Log(if subscribing {
+                            format!(
+                                "RTT probe got no SUBACK within {}s",
+                                rtt::RTT_PING_TIMEOUT.as_secs()
+                            )
+                        } else {
+                            format!("RTT ping timed out after {}s", rtt::RTT_PING_TIMEOUT.as_secs())
+                        }));
+                        advance_rtt_probe(&endpoint, &mut rtt_probe, &mut pending_unsubscribe, &event_tx).await;
+                    }
+                }
                 maybe_command = command_rx.recv() => {
                     let Some(command) = maybe_command else {
                         continue;
                     };
+                    keep_alive.sent(Instant::now());
 
                     match command {
                         ClientCommand::Disconnect => {
-                            let disconnect_packet = mqtt_ep::packet::v5_0::Disconnect::builder()
-                                .build();
-
-                            if let Ok(packet) = disconnect_packet {
-                                let _ = endpoint.send(packet).await;
-                            }
-
+                            send_disconnect(&endpoint, false, &event_tx).await;
                             let _ = endpoint.close().await;
                             let _ = event_tx.send(ClientEvent::Disconnected(
-                                "Disconnected by user".to_string(),
+                                ClientError::ClosedByUser("Disconnected by user".to_string()),
                             ));
                             break;
                         }
                         ClientCommand::ForceDisconnect => {
                             let _ = endpoint.close().await;
                             let _ = event_tx.send(ClientEvent::Disconnected(
-                                "Force disconnected by user".to_string(),
+                                ClientError::ClosedByUser("Force disconnected by user".to_string()),
                             ));
                             break;
                         }
                         ClientCommand::Subscribe { topic, qos } => {
-                            let qos_level = match mqtt_ep::packet::Qos::try_from(qos) {
-                                Ok(level) => level,
-                                Err(err) => {
-                                    let _ = event_tx.send(ClientEvent::Error(format!("Invalid subscribe QoS {qos}: {err}")));
-                                    continue;
+                            let entries = vec![(topic, qos)];
+                            if let Some(packet_id) = send_subscribe(&endpoint, &entries, &event_tx).await {
+                                for (topic, _) in &entries {
+                                    recent_subscriptions.push((topic.clone(), Instant::now()));
                                 }
-                            };
-
-                            let packet_id = match endpoint.acquire_packet_id().await {
-                                Ok(id) => id,
-                                Err(err) => {
-                                    let _ = event_tx.send(ClientEvent::Error(format!("Failed to acquire packet id: {err}")));
-                                    continue;
-                                }
-                            };
-
-                            let sub_opts = mqtt_ep::packet::SubOpts::new().set_qos(qos_level);
-                            let sub_entry = match mqtt_ep::packet::SubEntry::new(&topic, sub_opts) {
-                                Ok(entry) => entry,
-                                Err(err) => {
-                                    let _ = event_tx.send(ClientEvent::Error(format!("Invalid subscription topic '{topic}': {err}")));
-                                    continue;
-                                }
-                            };
-
-                            let subscribe_packet = match mqtt_ep::packet::v5_0::Subscribe::builder()
-                                .packet_id(packet_id)
-                                .entries(vec![sub_entry])
-                                .build()
-                            {
-                                Ok(packet) => packet,
-                                Err(err) => {
-                                    let _ = event_tx.send(ClientEvent::Error(format!("Failed to build SUBSCRIBE: {err}")));
-                                    continue;
-                                }
-                            };
-
-                            if let Err(err) = endpoint.send(subscribe_packet).await {
-                                let _ = event_tx.send(ClientEvent::Error(format!("Failed to send SUBSCRIBE: {err}")));
+                                pending_subscribe.insert(packet_id, PendingSubscribe { entries, sent_at: Instant::now() });
+                            }
+                        }
+                        ClientCommand::SubscribeMany { entries } => {
+                            if entries.is_empty() {
                                 continue;
                             }
-
-                            pending_subscribe.insert(packet_id, (topic, qos));
+                            if let Some(packet_id) = send_subscribe(&endpoint, &entries, &event_tx).await {
+                                for (topic, _) in &entries {
+                                    recent_subscriptions.push((topic.clone(), Instant::now()));
+                                }
+                                pending_subscribe.insert(packet_id, PendingSubscribe { entries, sent_at: Instant::now() });
+                            }
                         }
                         ClientCommand::Unsubscribe { topic } => {
-                            let packet_id = match endpoint.acquire_packet_id().await {
-                                Ok(id) => id,
-                                Err(err) => {
-                                    let _ = event_tx.send(ClientEvent::Error(format!("Failed to acquire packet id: {err}")));
-                                    continue;
+                            if let Some(packet_id) = send_unsubscribe(&endpoint, &topic, &event_tx).await {
+                                pending_unsubscribe.insert(packet_id, topic);
+                            }
+                        }
+                        ClientCommand::Ack { packet_id } => {
+                            match manual_acks.remove(&packet_id) {
+                                Some(ack) => {
+                                    send_inbound_ack(&endpoint, ack, &event_tx).await;
+                                    let _ = event_tx.send(ClientEvent::Log(format!(
+                                        "{} sent manually for packet id {packet_id}",
+                                        ack.packet_name()
+                                    )));
+                                    let _ = event_tx.send(ClientEvent::Acked { packet_id });
                                 }
-                            };
-
-                            let unsubscribe_packet = match mqtt_ep::packet::v5_0::Unsubscribe::builder()
-                                .packet_id(packet_id)
-                                .entries(vec![topic.as_str()])
-                                .and_then(|builder| builder.build())
-                            {
-                                Ok(packet) => packet,
-                                Err(err) => {
-                                    let _ = event_tx.send(ClientEvent::Error(format!("Failed to build UNSUBSCRIBE: {err}")));
-                                    continue;
+                                None => {
+                                    let _ = event_tx.send(ClientEvent::Log(format!(
+                                        "No pending ack for packet id {packet_id}"
+                                    )));
                                 }
-                            };
-
-                            if let Err(err) = endpoint.send(unsubscribe_packet).await {
-                                let _ = event_tx.send(ClientEvent::Error(format!("Failed to send UNSUBSCRIBE: {err}")));
+                            }
+                        }
+                        ClientCommand::MeasureRtt { count } => {
+                            if rtt_probe.is_some() {
+                                let _ = event_tx.send(ClientEvent::Log("RTT measurement already running".to_string()));
                                 continue;
                             }
-
-                            pending_unsubscribe.insert(packet_id, topic);
+                            let topic = rtt::probe_topic(tab_id);
+                            if let Some(packet_id) = send_subscribe(&endpoint, &[(topic.clone(), 0)], &event_tx).await {
+                                let _ = event_tx.send(ClientEvent::Log(format!("Measuring RTT with {count} pings on '{topic}'")));
+                                let mut probe = RttProbe::new(topic, count);
+                                probe.subscribing(packet_id, Instant::now());
+                                rtt_probe = Some(probe);
+                            }
+                        }
+                        ClientCommand::SetAckMode { mode, delay } => {
+                            ack_mode = mode;
+                            ack_delay = delay;
+                            let _ = event_tx.send(ClientEvent::Log(match mode {
+                                AckMode::Delayed => format!("Inbound acks delayed by {}ms", delay.as_millis()),
+                                _ => format!("Inbound acks: {}", mode.label()),
+                            }));
                         }
                         ClientCommand::Publish {
                             topic,
                             payload,
                             qos,
                             retain,
+                            properties,
                         } => {
                             let qos_level = match mqtt_ep::packet::Qos::try_from(qos) {
                                 Ok(level) => level,
                                 Err(err) => {
-                                    let _ = event_tx.send(ClientEvent::Error(format!("Invalid publish QoS {qos}: {err}")));
+                                    let _ = event_tx.send(ClientEvent::Error(ClientError::ProtocolError(format!("Invalid publish QoS {qos}: {err}"))));
+                                    continue;
+                                }
+                            };
+                            let props = match build_publish_properties(&properties) {
+                                Ok(props) => props,
+                                Err(err) => {
+                                    let _ = event_tx.send(ClientEvent::Error(ClientError::ProtocolError(err)));
                                     continue;
                                 }
                             };
 
+                            let payload_len = payload.len();
                             let mut builder = match mqtt_ep::packet::v5_0::Publish::builder()
                                 .topic_name(&topic)
                             {
                                 Ok(builder) => builder,
                                 Err(err) => {
-                                    let _ = event_tx.send(ClientEvent::Error(format!("Invalid publish topic '{topic}': {err}")));
+                                    let _ = event_tx.send(ClientEvent::Error(ClientError::ProtocolError(format!("Invalid publish topic '{topic}': {}", describe_mqtt_error(err)))));
                                     continue;
                                 }
                             }
                             .qos(qos_level)
                             .retain(retain)
+                            .props(props)
                             .payload(payload);
 
                             let mut packet_id = None;
@@ -496,7 +1018,7 @@ pub(crate) fn spawn_client(runtime: &Runtime, tab_id: u64, login: MqttLoginData)
                                 let id = match endpoint.acquire_packet_id().await {
                                     Ok(id) => id,
                                     Err(err) => {
-                                        let _ = event_tx.send(ClientEvent::Error(format!("Failed to acquire packet id: {err}")));
+                                        let _ = event_tx.send(ClientEvent::Error(ClientError::ProtocolError(format!("Failed to acquire packet id: {}", describe_endpoint_error(&err)))));
                                         continue;
                                     }
                                 };
@@ -504,23 +1026,41 @@ pub(crate) fn spawn_client(runtime: &Runtime, tab_id: u64, login: MqttLoginData)
                                 packet_id = Some(id);
                             }
 
+                            let publish_summary = format!(
+                                "PUBLISH{} topic={topic} qos={qos} retain={retain} bytes={payload_len}",
+                                packet_id.map(|id| format!(" id={id}")).unwrap_or_default()
+                            );
                             let publish_packet = match builder.build() {
                                 Ok(packet) => packet,
                                 Err(err) => {
-                                    let _ = event_tx.send(ClientEvent::Error(format!("Failed to build PUBLISH: {err}")));
+                                    let _ = event_tx.send(ClientEvent::Error(ClientError::ProtocolError(format!("Failed to build PUBLISH: {}", describe_mqtt_error(err)))));
                                     continue;
                                 }
                             };
 
                             if let Err(err) = endpoint.send(publish_packet).await {
-                                let _ = event_tx.send(ClientEvent::Error(format!("Failed to send PUBLISH: {err}")));
+                                let _ = event_tx.send(ClientEvent::Error(endpoint_failure("Failed to send PUBLISH", &err)));
                                 continue;
                             }
+                            let _ = event_tx.send(ClientEvent::PacketSent(publish_summary));
+                            if let Some(id) = packet_id.filter(|_| qos_level == mqtt_ep::packet::Qos::ExactlyOnce) {
+                                let _ = event_tx.send(ClientEvent::Log(format!("QoS 2 out: PUBLISH sent (packet id {id})")));
+                            }
+
+                            let request = properties
+                                .correlation_data
+                                .as_deref()
+                                .map(|data| correlations.record(data));
 
                             if let Some(id) = packet_id {
-                                pending_publish.insert(id, (topic.clone(), qos_level == mqtt_ep::packet::Qos::ExactlyOnce));
+                                pending_publish.insert(id, PendingPublish {
+                                    topic,
+                                    exactly_once: qos_level == mqtt_ep::packet::Qos::ExactlyOnce,
+                                    request,
+                                    sent_at: Instant::now(),
+                                });
                             } else {
-                                let _ = event_tx.send(ClientEvent::Published { topic, packet_id: None });
+                                let _ = event_tx.send(ClientEvent::Published { topic, packet_id: None, request, ack_elapsed: None });
                             }
                         }
                     }
@@ -529,11 +1069,12 @@ pub(crate) fn spawn_client(runtime: &Runtime, tab_id: u64, login: MqttLoginData)
                     let packet = match recv_result {
                         Ok(packet) => packet,
                         Err(err) => {
-                            let _ = event_tx.send(ClientEvent::Disconnected(format!("Receive loop failed: {err}")));
+                            let _ = event_tx.send(ClientEvent::Disconnected(receive_failure(&err)));
                             let _ = endpoint.close().await;
                             break;
                         }
                     };
+                    keep_alive.received(Instant::now());
 
                     match packet {
                         mqtt_ep::packet::Packet::V5_0Publish(publish) => {
@@ -541,62 +1082,122 @@ pub(crate) fn spawn_client(runtime: &Runtime, tab_id: u64, login: MqttLoginData)
                             let topic = publish.topic_name().to_string();
                             let qos_level = publish.qos();
                             let retain = publish.retain();
+                            let properties = message_properties_from(publish.props());
+                            let reply_to = properties
+                                .correlation_data
+                                .as_deref()
+                                .and_then(|data| correlations.lookup(data));
+                            recent_subscriptions
+                                .retain(|(_, acked_at)| acked_at.elapsed() < RETAINED_ON_SUBSCRIBE_WINDOW);
+                            let retained_on_subscribe = retain
+                                && recent_subscriptions
+                                    .iter()
+                                    .any(|(filter, _)| topic_matches(filter, &topic));
+
+                            // Probe pings are measured here and never shown as received messages.
+                            if let Some(probe) = rtt_probe.as_mut()
+                                && probe.topic == topic
+                            {
+                                if let Some(rtt) = probe.echoed(&payload, Instant::now()) {
+                                    let _ = event_tx.send(ClientEvent::RttSample(rtt));
+                                    advance_rtt_probe(&endpoint, &mut rtt_probe, &mut pending_unsubscribe, &event_tx).await;
+                                }
+                                continue;
+                            }
 
-                            let _ = event_tx.send(ClientEvent::MessageReceived {
+                            let packet_id = publish.packet_id();
+                            let ack_pending = packet_id.is_some() && ack_mode == AckMode::Manual;
+                            let message = ReceivedMessage {
+                                timestamp: SystemTime::now(),
                                 topic: topic.clone(),
                                 qos: qos_to_u8(qos_level),
                                 retain,
                                 payload,
-                            });
+                                properties,
+                                reply_to,
+                                retained_on_subscribe,
+                                packet_id,
+                                ack_pending,
+                            };
+                            // Messages waiting for a manual ack are never held back or dropped.
+                            if ui_hidden.load(Ordering::Relaxed) && !ack_pending {
+                                pending_messages.flush(&event_tx);
+                                hidden_backlog.push(message);
+                            } else {
+                                hidden_backlog.flush(&event_tx);
+                                if pending_messages.push(message, Instant::now()) {
+                                    pending_messages.flush(&event_tx);
+                                }
+                            }
 
-                            match qos_level {
-                                mqtt_ep::packet::Qos::AtMostOnce => {}
-                                mqtt_ep::packet::Qos::AtLeastOnce => {
-                                    if let Some(packet_id) = publish.packet_id() {
-                                        let puback = match mqtt_ep::packet::v5_0::Puback::builder()
-                                            .packet_id(packet_id)
-                                            .build()
-                                        {
-                                            Ok(packet) => packet,
-                                            Err(err) => {
-                                                let _ = event_tx.send(ClientEvent::Error(format!("Failed to build PUBACK: {err}")));
-                                                continue;
-                                            }
-                                        };
-
-                                        if let Err(err) = endpoint.send(puback).await {
-                                            let _ = event_tx.send(ClientEvent::Error(format!("Failed to send PUBACK: {err}")));
-                                        }
-                                    }
+                            let exactly_once = qos_level == mqtt_ep::packet::Qos::ExactlyOnce;
+                            if let Some(packet_id) = packet_id {
+                                if exactly_once {
+                                    let _ = event_tx.send(ClientEvent::Log(format!("QoS 2 in: PUBLISH received (packet id {packet_id})")));
                                 }
-                                mqtt_ep::packet::Qos::ExactlyOnce => {
-                                    if let Some(packet_id) = publish.packet_id() {
-                                        let pubrec = match mqtt_ep::packet::v5_0::Pubrec::builder()
-                                            .packet_id(packet_id)
-                                            .build()
-                                        {
-                                            Ok(packet) => packet,
-                                            Err(err) => {
-                                                let _ = event_tx.send(ClientEvent::Error(format!("Failed to build PUBREC: {err}")));
-                                                continue;
-                                            }
-                                        };
-
-                                        if let Err(err) = endpoint.send(pubrec).await {
-                                            let _ = event_tx.send(ClientEvent::Error(format!("Failed to send PUBREC: {err}")));
-                                        }
+                                let ack = InboundAck { packet_id, exactly_once };
+                                match ack_mode {
+                                    AckMode::Auto => send_inbound_ack(&endpoint, ack, &event_tx).await,
+                                    AckMode::Delayed => {
+                                        let _ = event_tx.send(ClientEvent::Log(format!(
+                                            "{} for packet id {packet_id} delayed by {}ms",
+                                            ack.packet_name(),
+                                            ack_delay.as_millis()
+                                        )));
+                                        delayed_acks.push(Instant::now() + ack_delay, ack);
+                                    }
+                                    AckMode::Suppressed => {
+                                        let _ = event_tx.send(ClientEvent::Log(format!(
+                                            "{} for packet id {packet_id} suppressed",
+                                            ack.packet_name()
+                                        )));
+                                    }
+                                    AckMode::Manual => {
+                                        manual_acks.insert(packet_id, ack);
                                     }
                                 }
                             }
                         }
                         mqtt_ep::packet::Packet::V5_0Suback(suback) => {
                             let packet_id = suback.packet_id();
-                            if let Some((topic, qos)) = pending_subscribe.remove(&packet_id) {
-                                let _ = event_tx.send(ClientEvent::Subscribed {
-                                    topic,
-                                    qos,
-                                    details: format!("{:?}", suback.reason_codes()),
-                                });
+                            if rtt_probe.as_mut().is_some_and(|probe| probe.subscribed(packet_id)) {
+                                match suback.reason_codes().first().map(|code| *code as u8) {
+                                    Some(code) if code < 0x80 => {
+                                        advance_rtt_probe(&endpoint, &mut rtt_probe, &mut pending_unsubscribe, &event_tx).await;
+                                    }
+                                    code => {
+                                        let _ = event_tx.send(ClientEvent::Log(format!(
+                                            "RTT probe subscription rejected: {}",
+                                            code.map_or("no reason code".to_string(), |code| describe_reason(code, suback_reason_name))
+                                        )));
+                                        if let Some(mut probe) = rtt_probe.take() {
+                                            probe.give_up();
+                                            let _ = event_tx.send(ClientEvent::RttFinished(probe.summary()));
+                                        }
+                                    }
+                                }
+                            } else if let Some(pending) = pending_subscribe.remove(&packet_id) {
+                                let ack_elapsed = pending.sent_at.elapsed();
+                                let codes: Vec<u8> = suback.reason_codes().iter().map(|code| *code as u8).collect();
+                                for outcome in match_suback(pending.entries, &codes) {
+                                    recent_subscriptions.retain(|(filter, _)| filter != &outcome.topic);
+                                    recent_subscriptions.push((outcome.topic.clone(), Instant::now()));
+                                    let _ = event_tx.send(match outcome.result {
+                                        Ok(granted_qos) => ClientEvent::Subscribed {
+                                            topic: outcome.topic,
+                                            qos: outcome.qos,
+                                            granted_qos,
+                                            packet_id,
+                                            details: describe_reason(granted_qos, suback_reason_name),
+                                            ack_elapsed,
+                                        },
+                                        Err(reason) => ClientEvent::SubscribeFailed {
+                                            topic: outcome.topic,
+                                            packet_id,
+                                            reason,
+                                        },
+                                    });
+                                }
                             } else {
                                 let _ = event_tx.send(ClientEvent::Status(format!(
                                     "SUBACK for unknown packet id {packet_id}"
@@ -606,9 +1207,13 @@ pub(crate) fn spawn_client(runtime: &Runtime, tab_id: u64, login: MqttLoginData)
                         mqtt_ep::packet::Packet::V5_0Unsuback(unsuback) => {
                             let packet_id = unsuback.packet_id();
                             if let Some(topic) = pending_unsubscribe.remove(&packet_id) {
+                                if topic == rtt::probe_topic(tab_id) {
+                                    continue;
+                                }
                                 let _ = event_tx.send(ClientEvent::Unsubscribed {
                                     topic,
-                                    details: format!("{:?}", unsuback.reason_codes()),
+                                    packet_id,
+                                    details: unsuback.reason_codes().iter().map(|code| describe_reason(*code as u8, reason_name)).collect::<Vec<_>>().join(", "),
                                 });
                             } else {
                                 let _ = event_tx.send(ClientEvent::Status(format!(
@@ -618,17 +1223,45 @@ pub(crate) fn spawn_client(runtime: &Runtime, tab_id: u64, login: MqttLoginData)
                         }
                         mqtt_ep::packet::Packet::V5_0Puback(puback) => {
                             let packet_id = puback.packet_id();
-                            if let Some((topic, _)) = pending_publish.remove(&packet_id) {
+                            if let Some(pending) = pending_publish.remove(&packet_id) {
+                                if let Some(code) = puback.reason_code().filter(|code| *code as u8 != 0) {
+                                    let _ = event_tx.send(ClientEvent::Log(format!(
+                                        "PUBACK for '{}': {}",
+                                        pending.topic,
+                                        describe_reason(code as u8, reason_name)
+                                    )));
+                                }
                                 let _ = event_tx.send(ClientEvent::Published {
-                                    topic,
+                                    topic: pending.topic,
                                     packet_id: Some(packet_id),
+                                    request: pending.request,
+                                    ack_elapsed: Some(pending.sent_at.elapsed()),
                                 });
                             }
                         }
                         mqtt_ep::packet::Packet::V5_0Pubrec(pubrec) => {
                             let packet_id = pubrec.packet_id();
-                            if let Some((_, waiting_for_pubcomp)) = pending_publish.get_mut(&packet_id)
-                                && *waiting_for_pubcomp
+                            let _ = event_tx.send(ClientEvent::Log(format!("QoS 2 out: PUBREC received (packet id {packet_id})")));
+                            // A failed PUBREC ends the exchange; the library has already freed the packet id.
+                            if let Some(code) = pubrec.reason_code().filter(|code| *code as u8 >= 0x80) {
+                                if let Some(pending) = pending_publish.remove(&packet_id) {
+                                    let _ = event_tx.send(ClientEvent::Log(format!(
+                                        "PUBREC for '{}': {}",
+                                        pending.topic,
+                                        describe_reason(code as u8, reason_name)
+                                    )));
+                                    let _ = event_tx.send(ClientEvent::Published {
+                                        topic: pending.topic,
+                                        packet_id: Some(packet_id),
+                                        request: pending.request,
+                                        ack_elapsed: Some(pending.sent_at.elapsed()),
+                                    });
+                                }
+                                continue;
+                            }
+                            // Automatic pub responses are off, so this is the only PUBREL sent.
+                            if let Some(pending) = pending_publish.get(&packet_id)
+                                && pending.exactly_once
                             {
                                 let pubrel = match mqtt_ep::packet::v5_0::Pubrel::builder()
                                     .packet_id(packet_id)
@@ -636,30 +1269,79 @@ pub(crate) fn spawn_client(runtime: &Runtime, tab_id: u64, login: MqttLoginData)
                                 {
                                     Ok(packet) => packet,
                                     Err(err) => {
-                                        let _ = event_tx.send(ClientEvent::Error(format!("Failed to build PUBREL: {err}")));
+                                        let _ = event_tx.send(ClientEvent::Error(ClientError::ProtocolError(format!("Failed to build PUBREL: {}", describe_mqtt_error(err)))));
                                         continue;
                                     }
                                 };
 
                                 if let Err(err) = endpoint.send(pubrel).await {
-                                    let _ = event_tx.send(ClientEvent::Error(format!("Failed to send PUBREL: {err}")));
+                                    let _ = event_tx.send(ClientEvent::Error(endpoint_failure("Failed to send PUBREL", &err)));
+                                } else {
+                                    let _ = event_tx.send(ClientEvent::PacketSent(format!("PUBREL id={packet_id}")));
+                                    let _ = event_tx.send(ClientEvent::Log(format!("QoS 2 out: PUBREL sent (packet id {packet_id})")));
                                 }
                             }
                         }
+                        mqtt_ep::packet::Packet::V5_0Pubrel(pubrel) => {
+                            let packet_id = pubrel.packet_id();
+                            let _ = event_tx.send(ClientEvent::Log(format!("QoS 2 in: PUBREL received (packet id {packet_id})")));
+                            // Automatic pub responses are off, so this is the only PUBCOMP sent.
+                            let pubcomp = match mqtt_ep::packet::v5_0::Pubcomp::builder()
+                                .packet_id(packet_id)
+                                .build()
+                            {
+                                Ok(packet) => packet,
+                                Err(err) => {
+                                    let _ = event_tx.send(ClientEvent::Error(ClientError::ProtocolError(format!("Failed to build PUBCOMP: {}", describe_mqtt_error(err)))));
+                                    continue;
+                                }
+                            };
+
+                            if let Err(err) = endpoint.send(pubcomp).await {
+                                let _ = event_tx.send(ClientEvent::Error(endpoint_failure("Failed to send PUBCOMP", &err)));
+                            } else {
+                                let _ = event_tx.send(ClientEvent::PacketSent(format!("PUBCOMP id={packet_id}")));
+                                let _ = event_tx.send(ClientEvent::Log(format!("QoS 2 in: PUBCOMP sent (packet id {packet_id})")));
+                            }
+                        }
                         mqtt_ep::packet::Packet::V5_0Pubcomp(pubcomp) => {
                             let packet_id = pubcomp.packet_id();
-                            if let Some((topic, _)) = pending_publish.remove(&packet_id) {
+                            let _ = event_tx.send(ClientEvent::Log(format!("QoS 2 out: PUBCOMP received (packet id {packet_id})")));
+                            if let Some(pending) = pending_publish.remove(&packet_id) {
                                 let _ = event_tx.send(ClientEvent::Published {
-                                    topic,
+                                    topic: pending.topic,
                                     packet_id: Some(packet_id),
+                                    request: pending.request,
+                                    ack_elapsed: Some(pending.sent_at.elapsed()),
                                 });
                             }
                         }
+                        mqtt_ep::packet::Packet::V5_0Pingresp(_) => {
+                            let _ = event_tx.send(ClientEvent::PingResponse);
+                        }
                         mqtt_ep::packet::Packet::V5_0Disconnect(disconnect) => {
-                            let _ = event_tx.send(ClientEvent::Disconnected(format!(
-                                "Broker disconnected: {:?}",
-                                disconnect.reason_code()
-                            )));
+                            let reason_string = disconnect.props().iter().flatten().find_map(|prop| match prop {
+                                mqtt_ep::packet::Property::ReasonString(reason) => Some(reason.val().to_string()),
+                                _ => None,
+                            });
+                            let reason_code = disconnect.reason_code();
+                            if reason_code == Some(DisconnectReasonCode::SessionTakenOver) {
+                                let _ = event_tx.send(ClientEvent::Disconnected(ClientError::SessionTakenOver {
+                                    client_id: client_id.clone(),
+                                }));
+                                let _ = endpoint.close().await;
+                                break;
+                            }
+                            let reason = match (reason_code, reason_string) {
+                                (Some(code), Some(text)) => format!("Broker disconnected: {}: {text}", disconnect_reason_name(code as u8)),
+                                (Some(code), None) => format!("Broker disconnected: {}", disconnect_reason_name(code as u8)),
+                                (None, Some(text)) => format!("Broker disconnected: {text}"),
+                                (None, None) => "Broker disconnected: Normal Disconnection".to_string(),
+                            };
+                            let _ = event_tx.send(ClientEvent::Disconnected(ClientError::BrokerDisconnect {
+                                code: reason_code.map(|code| code as u8).unwrap_or_default(),
+                                reason,
+                            }));
                             let _ = endpoint.close().await;
                             break;
                         }
@@ -673,12 +1355,77 @@ pub(crate) fn spawn_client(runtime: &Runtime, tab_id: u64, login: MqttLoginData)
                 }
             }
         }
+        pending_messages.flush(&event_tx);
+        hidden_backlog.flush(&event_tx);
     });
 
     ClientHandle {
         shutdown_tx: Some(shutdown_tx),
         join_handle,
+        ui_hidden,
         event_rx,
         command_tx,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use mqtt_endpoint_tokio::mqtt_ep;
+    use mqtt_endpoint_tokio::mqtt_ep::packet::{Properties, ReasonString, UserProperty};
+    use mqtt_endpoint_tokio::mqtt_ep::result_code::ConnectReasonCode;
+
+    use super::{connack_failure, receive_failure};
+    use crate::models::error::ClientError;
+
+    #[test]
+    fn auth_reason_codes_are_reported_as_auth_failures() {
+        let props = Properties::new();
+        for code in [
+            ConnectReasonCode::BadUserNameOrPassword,
+            ConnectReasonCode::NotAuthorized,
+            ConnectReasonCode::BadAuthenticationMethod,
+        ] {
+            assert!(matches!(
+                connack_failure(code, &props),
+                Some(ClientError::AuthFailed(_))
+            ));
+        }
+        assert_eq!(connack_failure(ConnectReasonCode::Success, &props), None);
+    }
+
+    #[test]
+    fn connack_failure_includes_reason_string_and_user_properties() {
+        let props: Properties = vec![
+            ReasonString::new("maintenance").unwrap().into(),
+            UserProperty::new("retry-after", "30").unwrap().into(),
+        ];
+
+        let err = connack_failure(ConnectReasonCode::ServerUnavailable, &props).unwrap();
+        assert!(err.is_retryable());
+        assert_eq!(
+            err.to_string(),
+            "Broker rejected CONNECT: Server Unavailable, reason: maintenance, user properties: retry-after=30 (reason code 0x88)"
+        );
+    }
+
+    #[test]
+    fn dropped_sockets_are_reported_as_closed_by_broker() {
+        let eof = mqtt_ep::ConnectionError::Transport(mqtt_ep::TransportError::Io(
+            std::io::Error::from(std::io::ErrorKind::ConnectionReset),
+        ));
+        for err in [mqtt_ep::ConnectionError::NotConnected, eof] {
+            assert_eq!(
+                receive_failure(&err),
+                ClientError::NetworkError(
+                    "Connection closed by broker (no DISCONNECT received)".to_string()
+                )
+            );
+        }
+        assert!(matches!(
+            receive_failure(&mqtt_ep::ConnectionError::Transport(
+                mqtt_ep::TransportError::Timeout
+            )),
+            ClientError::Timeout(_)
+        ));
+    }
+}
@@ -0,0 +1,322 @@
+use std::net::IpAddr;
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use crate::client::bind;
+use crate::models::error::ClientError;
+use crate::models::mqtt::{ProxyKind, ResolvedProxy};
+use crate::utils::formatting::encode_base64;
+
+const MAX_HTTP_RESPONSE_HEADER: usize = 16 * 1024;
+
+/// How long a proxy gets to answer the SOCKS5 or HTTP CONNECT handshake once the TCP
+/// connection is open.
+const PROXY_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub(crate) async fn connect_through_proxy(
+    proxy: &ResolvedProxy,
+    source: Option<IpAddr>,
+    host: &str,
+    port: u16,
+) -> Result<TcpStream, ClientError> {
+    let connected = match source {
+        Some(source) => bind::connect_from(&proxy.addr, source).await,
+        None => TcpStream::connect(&proxy.addr).await,
+    };
+    let mut stream = connected.map_err(|err| {
+        ClientError::NetworkError(format!(
+            "{} proxy connect to {} failed: {err}",
+            proxy.kind.label(),
+            proxy.addr
+        ))
+    })?;
+
+    handshake(&mut stream, proxy, host, port, PROXY_HANDSHAKE_TIMEOUT).await?;
+    Ok(stream)
+}
+
+async fn handshake(
+    stream: &mut TcpStream,
+    proxy: &ResolvedProxy,
+    host: &str,
+    port: u16,
+    timeout: Duration,
+) -> Result<(), ClientError> {
+    let exchange = async {
+        match proxy.kind {
+            ProxyKind::None => Err("Proxy connect requested without a proxy type".to_string()),
+            ProxyKind::Socks5 => socks5_handshake(stream, proxy, host, port)
+                .await
+                .map_err(|err| format!("SOCKS5 proxy {}: {err}", proxy.addr)),
+            ProxyKind::Http => http_connect(stream, proxy, host, port)
+                .await
+                .map_err(|err| format!("HTTP proxy {}: {err}", proxy.addr)),
+        }
+    };
+    match tokio::time::timeout(timeout, exchange).await {
+        Ok(result) => result.map_err(ClientError::NetworkError),
+        Err(_) => Err(ClientError::Timeout(format!(
+            "{} proxy {} did not answer the handshake within {}s",
+            proxy.kind.label(),
+            proxy.addr,
+            timeout.as_secs()
+        ))),
+    }
+}
+
+async fn socks5_handshake(
+    stream: &mut TcpStream,
+    proxy: &ResolvedProxy,
+    host: &str,
+    port: u16,
+) -> Result<(), String> {
+    let credentials = proxy
+        .username
+        .as_deref()
+        .map(|username| (username, proxy.password.as_deref().unwrap_or_default()));
+
+    let greeting: &[u8] = if credentials.is_some() {
+        &[0x05, 0x02, 0x00, 0x02]
+    } else {
+        &[0x05, 0x01, 0x00]
+    };
+    write_all(stream, greeting).await?;
+
+    let mut choice = [0u8; 2];
+    read_exact(stream, &mut choice).await?;
+    if choice[0] != 0x05 {
+        return Err(format!("unexpected protocol version {}", choice[0]));
+    }
+
+    match (choice[1], credentials) {
+        (0x00, _) => {}
+        (0x02, Some((username, password))) => {
+            write_all(stream, &socks5_auth_request(username, password)?).await?;
+            let mut status = [0u8; 2];
+            read_exact(stream, &mut status).await?;
+            if status[1] != 0x00 {
+                return Err("username/password authentication rejected".to_string());
+            }
+        }
+        (0x02, None) => return Err("proxy requires username/password authentication".to_string()),
+        (0xff, _) => return Err("no acceptable authentication method".to_string()),
+        (other, _) => return Err(format!("unsupported authentication method {other:#04x}")),
+    }
+
+    write_all(stream, &socks5_connect_request(host, port)?).await?;
+
+    let mut reply = [0u8; 4];
+    read_exact(stream, &mut reply).await?;
+    if reply[1] != 0x00 {
+        return Err(socks5_reply_message(reply[1]));
+    }
+
+    let bound_addr_len = match reply[3] {
+        0x01 => 4,
+        0x04 => 16,
+        0x03 => {
+            let mut len = [0u8; 1];
+            read_exact(stream, &mut len).await?;
+            usize::from(len[0])
+        }
+        other => return Err(format!("unexpected address type {other:#04x} in reply")),
+    };
+    let mut bound = vec![0u8; bound_addr_len + 2];
+    read_exact(stream, &mut bound).await
+}
+
+async fn http_connect(
+    stream: &mut TcpStream,
+    proxy: &ResolvedProxy,
+    host: &str,
+    port: u16,
+) -> Result<(), String> {
+    write_all(stream, http_connect_request(proxy, host, port).as_bytes()).await?;
+
+    let mut response = Vec::new();
+    let mut byte = [0u8; 1];
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() >= MAX_HTTP_RESPONSE_HEADER {
+            return Err("response header too large".to_string());
+        }
+        read_exact(stream, &mut byte).await?;
+        response.push(byte[0]);
+    }
+
+    let response = String::from_utf8_lossy(&response);
+    let status_line = response.lines().next().unwrap_or_default();
+    match parse_http_status(status_line)? {
+        200..=299 => Ok(()),
+        407 => Err("proxy authentication required (407)".to_string()),
+        _ => Err(format!("CONNECT rejected: {status_line}")),
+    }
+}
+
+fn socks5_auth_request(username: &str, password: &str) -> Result<Vec<u8>, String> {
+    let username_len = u8::try_from(username.len())
+        .map_err(|_| "username is longer than 255 bytes".to_string())?;
+    let password_len = u8::try_from(password.len())
+        .map_err(|_| "password is longer than 255 bytes".to_string())?;
+
+    let mut request = vec![0x01, username_len];
+    request.extend_from_slice(username.as_bytes());
+    request.push(password_len);
+    request.extend_from_slice(password.as_bytes());
+    Ok(request)
+}
+
+fn socks5_connect_request(host: &str, port: u16) -> Result<Vec<u8>, String> {
+    let mut request = vec![0x05, 0x01, 0x00];
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => {
+            request.push(0x01);
+            request.extend_from_slice(&ip.octets());
+        }
+        Ok(IpAddr::V6(ip)) => {
+            request.push(0x04);
+            request.extend_from_slice(&ip.octets());
+        }
+        Err(_) => {
+            let len = u8::try_from(host.len())
+                .map_err(|_| format!("host name '{host}' is longer than 255 bytes"))?;
+            request.push(0x03);
+            request.push(len);
+            request.extend_from_slice(host.as_bytes());
+        }
+    }
+    request.extend_from_slice(&port.to_be_bytes());
+    Ok(request)
+}
+
+fn socks5_reply_message(code: u8) -> String {
+    let reason = match code {
+        0x01 => "general failure",
+        0x02 => "connection not allowed by ruleset",
+        0x03 => "network unreachable",
+        0x04 => "host unreachable",
+        0x05 => "connection refused by destination",
+        0x06 => "TTL expired",
+        0x07 => "command not supported",
+        0x08 => "address type not supported",
+        _ => "unknown error",
+    };
+    format!("connect failed: {reason} ({code:#04x})")
+}
+
+fn http_connect_request(proxy: &ResolvedProxy, host: &str, port: u16) -> String {
+    let authority = if host.contains(':') {
+        format!("[{host}]:{port}")
+    } else {
+        format!("{host}:{port}")
+    };
+
+    let mut request = format!("CONNECT {authority} HTTP/1.1\r\nHost: {authority}\r\n");
+    if let Some(username) = proxy.username.as_deref() {
+        let credentials = format!(
+            "{username}:{}",
+            proxy.password.as_deref().unwrap_or_default()
+        );
+        request.push_str(&format!(
+            "Proxy-Authorization: Basic {}\r\n",
+            encode_base64(credentials.as_bytes())
+        ));
+    }
+    request.push_str("\r\n");
+    request
+}
+
+fn parse_http_status(status_line: &str) -> Result<u16, String> {
+    let mut parts = status_line.split_whitespace();
+    match (parts.next(), parts.next()) {
+        (Some(version), Some(code)) if version.starts_with("HTTP/") => code
+            .parse()
+            .map_err(|_| format!("invalid status line '{status_line}'")),
+        _ => Err(format!("invalid status line '{status_line}'")),
+    }
+}
+
+async fn write_all(stream: &mut TcpStream, bytes: &[u8]) -> Result<(), String> {
+    stream
+        .write_all(bytes)
+        .await
+        .map_err(|err| format!("write failed: {err}"))
+}
+
+async fn read_exact(stream: &mut TcpStream, buf: &mut [u8]) -> Result<(), String> {
+    stream
+        .read_exact(buf)
+        .await
+        .map(|_| ())
+        .map_err(|err| format!("read failed: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::net::{TcpListener, TcpStream};
+
+    use super::{handshake, parse_http_status, socks5_connect_request};
+    use crate::models::error::ClientError;
+    use crate::models::mqtt::{ProxyKind, ResolvedProxy};
+
+    #[test]
+    fn socks5_requests_encode_address_types() {
+        assert_eq!(
+            socks5_connect_request("10.0.0.1", 1883).unwrap(),
+            [0x05, 0x01, 0x00, 0x01, 10, 0, 0, 1, 0x07, 0x5b]
+        );
+        assert_eq!(
+            socks5_connect_request("broker", 8883).unwrap(),
+            [
+                0x05, 0x01, 0x00, 0x03, 6, b'b', b'r', b'o', b'k', b'e', b'r', 0x22, 0xb3
+            ]
+        );
+        assert_eq!(socks5_connect_request("::1", 1).unwrap()[3], 0x04);
+    }
+
+    #[test]
+    fn http_status_line_is_parsed() {
+        assert_eq!(
+            parse_http_status("HTTP/1.1 200 Connection established"),
+            Ok(200)
+        );
+        assert_eq!(
+            parse_http_status("HTTP/1.0 407 Proxy Authentication Required"),
+            Ok(407)
+        );
+        assert!(parse_http_status("garbage").is_err());
+    }
+
+    #[test]
+    fn silent_proxy_times_out() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap().to_string();
+            let mut stream = TcpStream::connect(&addr).await.unwrap();
+            let _accepted = listener.accept().await.unwrap();
+            let proxy = ResolvedProxy {
+                kind: ProxyKind::Socks5,
+                addr,
+                username: None,
+                password: None,
+            };
+
+            let result = handshake(
+                &mut stream,
+                &proxy,
+                "broker",
+                1883,
+                Duration::from_millis(50),
+            )
+            .await;
+            assert!(matches!(result, Err(ClientError::Timeout(_))));
+        });
+    }
+}
@@ -0,0 +1,151 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use tokio::runtime::Runtime;
+use tokio::sync::watch;
+use tokio::time::{Instant, sleep, sleep_until};
+
+use crate::models::client::{CommandSendError, CommandSender, ReplayControl, ReplayHandle};
+use crate::models::export::ExportedMessage;
+use crate::models::ipc::ClientCommand;
+
+const QUEUE_FULL_BACKOFF: Duration = Duration::from_millis(20);
+
+pub(crate) fn replay_schedule(
+    messages: &[ExportedMessage],
+    preserve_timing: bool,
+    speed: f64,
+) -> Result<Vec<(Duration, ClientCommand)>, String> {
+    if preserve_timing && !(speed.is_finite() && speed > 0.0) {
+        return Err(format!("Replay speed must be positive, got {speed}"));
+    }
+
+    let mut previous_ms = None;
+    messages
+        .iter()
+        .map(|message| {
+            let delay = match previous_ms {
+                Some(previous) if preserve_timing => {
+                    Duration::from_millis(message.timestamp_ms.saturating_sub(previous))
+                        .div_f64(speed)
+                }
+                _ => Duration::ZERO,
+            };
+            previous_ms = Some(message.timestamp_ms);
+            Ok((delay, message.to_publish_command()?))
+        })
+        .collect()
+}
+
+pub(crate) fn spawn_replay(
+    runtime: &Runtime,
+    schedule: Vec<(Duration, ClientCommand)>,
+    command_tx: CommandSender,
+) -> ReplayHandle {
+    let (control_tx, control_rx) = watch::channel(ReplayControl::Running);
+    let sent = Arc::new(AtomicUsize::new(0));
+    let total = schedule.len();
+    let join_handle = runtime.spawn(run_replay(
+        schedule,
+        command_tx,
+        control_rx,
+        Arc::clone(&sent),
+    ));
+
+    ReplayHandle {
+        control_tx,
+        sent,
+        total,
+        join_handle,
+    }
+}
+
+async fn run_replay(
+    schedule: Vec<(Duration, ClientCommand)>,
+    command_tx: CommandSender,
+    mut control_rx: watch::Receiver<ReplayControl>,
+    sent: Arc<AtomicUsize>,
+) {
+    for (delay, command) in schedule {
+        if !wait_for_slot(Instant::now() + delay, &mut control_rx).await {
+            return;
+        }
+        while let Err(err) = command_tx.try_send(command.clone()) {
+            if err == CommandSendError::Closed {
+                return;
+            }
+            sleep(QUEUE_FULL_BACKOFF).await;
+            if *control_rx.borrow() == ReplayControl::Stopped {
+                return;
+            }
+        }
+        sent.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+async fn wait_for_slot(deadline: Instant, control_rx: &mut watch::Receiver<ReplayControl>) -> bool {
+    loop {
+        let control = *control_rx.borrow_and_update();
+        match control {
+            ReplayControl::Stopped => return false,
+            ReplayControl::Paused => {
+                if control_rx.changed().await.is_err() {
+                    return false;
+                }
+            }
+            ReplayControl::Running => {
+                tokio::select! {
+                    _ = sleep_until(deadline) => return true,
+                    changed = control_rx.changed() => {
+                        if changed.is_err() {
+                            return false;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::replay_schedule;
+    use crate::models::export::{ExportedMessage, ExportedProperties};
+
+    fn message_at(timestamp_ms: u64) -> ExportedMessage {
+        ExportedMessage {
+            timestamp_ms,
+            topic: "replay/test".to_string(),
+            qos: 0,
+            retain: false,
+            properties: ExportedProperties::default(),
+            payload_base64: "AA==".to_string(),
+        }
+    }
+
+    #[test]
+    fn schedule_scales_gaps_by_speed() {
+        let messages = [message_at(1_000), message_at(3_000), message_at(2_000)];
+
+        let delays: Vec<_> = replay_schedule(&messages, true, 2.0)
+            .unwrap()
+            .into_iter()
+            .map(|(delay, _)| delay)
+            .collect();
+        assert_eq!(
+            delays,
+            [Duration::ZERO, Duration::from_secs(1), Duration::ZERO]
+        );
+
+        assert!(
+            replay_schedule(&messages, false, 2.0)
+                .unwrap()
+                .iter()
+                .all(|(delay, _)| delay.is_zero())
+        );
+        assert!(replay_schedule(&messages, true, 0.0).is_err());
+    }
+}
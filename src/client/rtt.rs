@@ -0,0 +1,214 @@
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use mqtt_endpoint_tokio::mqtt_ep;
+
+use crate::client::errors::{describe_mqtt_error, endpoint_failure};
+use crate::models::error::ClientError;
+use crate::models::ipc::ClientEvent;
+use crate::models::stats::RttSummary;
+
+pub(crate) const RTT_PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The topic a tab's probe pings itself on; unique per process and tab.
+pub(crate) fn probe_topic(tab_id: u64) -> String {
+    format!("mqui/rtt/{}-{tab_id}", std::process::id())
+}
+
+#[derive(Debug)]
+pub(crate) struct RttProbe {
+    pub(crate) topic: String,
+    /// The probe SUBSCRIBE still waiting for its SUBACK, and when it was sent.
+    subscribing: Option<(u16, Instant)>,
+    remaining: u32,
+    next_seq: u32,
+    in_flight: Option<(u32, Instant)>,
+    summary: RttSummary,
+}
+
+impl RttProbe {
+    pub(crate) fn new(topic: String, count: u32) -> Self {
+        Self {
+            topic,
+            subscribing: None,
+            remaining: count.max(1),
+            next_seq: 0,
+            in_flight: None,
+            summary: RttSummary::default(),
+        }
+    }
+
+    pub(crate) fn subscribing(&mut self, packet_id: u16, now: Instant) {
+        self.subscribing = Some((packet_id, now));
+    }
+
+    pub(crate) fn is_subscribing(&self) -> bool {
+        self.subscribing.is_some()
+    }
+
+    /// Returns true when the SUBACK belongs to the probe subscription, which arms the first ping.
+    pub(crate) fn subscribed(&mut self, packet_id: u16) -> bool {
+        if self.subscribing.is_none_or(|(id, _)| id != packet_id) {
+            return false;
+        }
+        self.subscribing = None;
+        true
+    }
+
+    /// When the pending SUBACK or the ping in flight gives up.
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        self.subscribing
+            .map(|(_, sent_at)| sent_at)
+            .or(self.in_flight.map(|(_, sent_at)| sent_at))
+            .map(|sent_at| sent_at + RTT_PING_TIMEOUT)
+    }
+
+    /// Starts the next ping and returns its payload, or `None` once every ping has been sent.
+    pub(crate) fn next_ping(&mut self, now: Instant) -> Option<Vec<u8>> {
+        if self.remaining == 0 || self.subscribing.is_some() {
+            return None;
+        }
+        self.remaining -= 1;
+        self.next_seq += 1;
+        self.in_flight = Some((self.next_seq, now));
+        Some(format!("mqui-rtt {}", self.next_seq).into_bytes())
+    }
+
+    /// Records an echoed ping and returns its round-trip time.
+    pub(crate) fn echoed(&mut self, payload: &[u8], now: Instant) -> Option<Duration> {
+        let seq = std::str::from_utf8(payload)
+            .ok()?
+            .strip_prefix("mqui-rtt ")?
+            .parse::<u32>()
+            .ok()?;
+        let (expected, sent_at) = self.in_flight?;
+        if seq != expected {
+            return None;
+        }
+
+        self.in_flight = None;
+        let rtt = now.saturating_duration_since(sent_at);
+        self.summary.record(rtt);
+        Some(rtt)
+    }
+
+    /// Gives up on an overdue ping, or on every ping if the SUBACK is overdue.
+    pub(crate) fn timed_out(&mut self, now: Instant) -> bool {
+        match self.deadline() {
+            Some(deadline) if deadline <= now => {
+                if self.subscribing.is_some() {
+                    self.give_up();
+                } else {
+                    self.in_flight = None;
+                    self.summary.lost += 1;
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Counts every ping not yet sent as lost and stops waiting for the SUBACK.
+    pub(crate) fn give_up(&mut self) {
+        self.subscribing = None;
+        self.summary.lost += self.remaining;
+        self.remaining = 0;
+    }
+
+    pub(crate) fn is_finished(&self) -> bool {
+        self.remaining == 0 && self.in_flight.is_none() && self.subscribing.is_none()
+    }
+
+    pub(crate) fn summary(&self) -> RttSummary {
+        self.summary
+    }
+}
+
+pub(crate) async fn send_ping(
+    endpoint: &mqtt_ep::endpoint::Endpoint<mqtt_ep::role::Client>,
+    topic: &str,
+    payload: Vec<u8>,
+    event_tx: &mpsc::Sender<ClientEvent>,
+) {
+    let packet = match mqtt_ep::packet::v5_0::Publish::builder().topic_name(topic) {
+        Ok(builder) => builder
+            .qos(mqtt_ep::packet::Qos::AtMostOnce)
+            .payload(payload)
+            .build(),
+        Err(err) => {
+            let _ = event_tx.send(ClientEvent::Error(ClientError::ProtocolError(format!(
+                "Invalid RTT topic '{topic}': {}",
+                describe_mqtt_error(err)
+            ))));
+            return;
+        }
+    };
+
+    let result = match packet {
+        Ok(packet) => endpoint.send(packet).await,
+        Err(err) => {
+            let _ = event_tx.send(ClientEvent::Error(ClientError::ProtocolError(format!(
+                "Failed to build RTT ping: {}",
+                describe_mqtt_error(err)
+            ))));
+            return;
+        }
+    };
+    match result {
+        Ok(()) => {
+            let _ = event_tx.send(ClientEvent::PacketSent(format!(
+                "PUBLISH topic={topic} qos=0 retain=false (RTT ping)"
+            )));
+        }
+        Err(err) => {
+            let _ = event_tx.send(ClientEvent::Error(endpoint_failure(
+                "Failed to send RTT ping",
+                &err,
+            )));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{RTT_PING_TIMEOUT, RttProbe};
+
+    #[test]
+    fn pings_wait_for_suback_and_match_sequence() {
+        let start = Instant::now();
+        let mut probe = RttProbe::new("mqui/rtt/1".to_string(), 2);
+        probe.subscribing(7, start);
+        assert_eq!(probe.next_ping(start), None);
+        assert!(!probe.subscribed(8));
+        assert!(probe.subscribed(7));
+
+        let ping = probe.next_ping(start).unwrap();
+        assert_eq!(probe.echoed(b"mqui-rtt 99", start), None);
+        assert_eq!(
+            probe.echoed(&ping, start + Duration::from_millis(12)),
+            Some(Duration::from_millis(12))
+        );
+
+        probe.next_ping(start).unwrap();
+        assert!(!probe.timed_out(start));
+        assert!(probe.timed_out(start + RTT_PING_TIMEOUT));
+        assert!(probe.is_finished());
+        assert_eq!(probe.summary().received, 1);
+        assert_eq!(probe.summary().lost, 1);
+    }
+
+    #[test]
+    fn missing_suback_ends_the_probe() {
+        let start = Instant::now();
+        let mut probe = RttProbe::new("mqui/rtt/1".to_string(), 3);
+        probe.subscribing(7, start);
+        assert_eq!(probe.deadline(), Some(start + RTT_PING_TIMEOUT));
+        assert!(!probe.timed_out(start));
+        assert!(probe.timed_out(start + RTT_PING_TIMEOUT));
+        assert!(probe.is_finished());
+        assert!(!probe.subscribed(7));
+        assert_eq!(probe.summary().lost, 3);
+    }
+}
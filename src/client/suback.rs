@@ -0,0 +1,48 @@
+use crate::utils::reason::{describe_reason, suback_reason_name};
+
+/// What the broker said about one topic filter of a SUBSCRIBE.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct SubackOutcome {
+    pub(crate) topic: String,
+    pub(crate) qos: u8,
+    /// The granted QoS, or why the filter was rejected.
+    pub(crate) result: Result<u8, String>,
+}
+
+/// Pairs the filters of a SUBSCRIBE with the SUBACK reason codes, which the broker returns in
+/// the same order. A filter without a code is treated as rejected.
+pub(crate) fn match_suback(entries: Vec<(String, u8)>, codes: &[u8]) -> Vec<SubackOutcome> {
+    entries
+        .into_iter()
+        .enumerate()
+        .map(|(index, (topic, qos))| {
+            let result = match codes.get(index) {
+                Some(&code) if code < 0x80 => Ok(code),
+                Some(&code) => Err(describe_reason(code, suback_reason_name)),
+                None => Err("no reason code in SUBACK".to_string()),
+            };
+            SubackOutcome { topic, qos, result }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::match_suback;
+
+    #[test]
+    fn reason_codes_map_to_topics_in_order() {
+        let entries = vec![
+            ("a".to_string(), 1),
+            ("b/#".to_string(), 2),
+            ("c".to_string(), 0),
+        ];
+        let outcomes = match_suback(entries, &[0x01, 0x87]);
+
+        assert_eq!(outcomes[0].topic, "a");
+        assert_eq!(outcomes[0].result, Ok(1));
+        assert_eq!(outcomes[1].topic, "b/#");
+        assert_eq!(outcomes[1].result, Err("0x87 Not Authorized".to_string()));
+        assert!(outcomes[2].result.is_err());
+    }
+}
@@ -0,0 +1,296 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::sync::{Arc, Once};
+
+use rustls::client::WantsClientCert;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::{
+    AlertDescription, ClientConfig, ConfigBuilder, DigitallySignedStruct, ProtocolVersion,
+    RootCertStore, SignatureScheme,
+};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use tokio_rustls::client::TlsStream;
+
+use crate::models::error::ClientError;
+use crate::models::mqtt::{MqttLoginData, TlsSessionInfo, TlsVerificationMode};
+
+static RUSTLS_PROVIDER_INIT: Once = Once::new();
+
+#[derive(Debug)]
+struct InsecureServerCertVerifier;
+
+impl ServerCertVerifier for InsecureServerCertVerifier {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        vec![
+            SignatureScheme::RSA_PKCS1_SHA1,
+            SignatureScheme::ECDSA_SHA1_Legacy,
+            SignatureScheme::RSA_PKCS1_SHA256,
+            SignatureScheme::ECDSA_NISTP256_SHA256,
+            SignatureScheme::RSA_PKCS1_SHA384,
+            SignatureScheme::ECDSA_NISTP384_SHA384,
+            SignatureScheme::RSA_PKCS1_SHA512,
+            SignatureScheme::ECDSA_NISTP521_SHA512,
+            SignatureScheme::RSA_PSS_SHA256,
+            SignatureScheme::RSA_PSS_SHA384,
+            SignatureScheme::RSA_PSS_SHA512,
+            SignatureScheme::ED25519,
+            SignatureScheme::ED448,
+        ]
+    }
+}
+
+fn ensure_rustls_crypto_provider() {
+    RUSTLS_PROVIDER_INIT.call_once(|| {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+    });
+}
+
+fn build_tls_config(login: &MqttLoginData, domain: &str) -> Result<Arc<ClientConfig>, String> {
+    if domain.trim().is_empty() {
+        return Err("TLS transport requires a non-empty server name".to_string());
+    }
+
+    ensure_rustls_crypto_provider();
+
+    let builder = match login.tls_verification {
+        TlsVerificationMode::SystemRoots => {
+            ClientConfig::builder().with_root_certificates(native_root_store())
+        }
+        TlsVerificationMode::CustomCa => {
+            let path = login.tls_ca_cert_path.trim();
+            if path.is_empty() {
+                return Err("Custom CA verification requires a CA PEM file path".to_string());
+            }
+
+            let mut root_store = native_root_store();
+            let certs = load_certificates(path, "CA")?;
+            for cert in certs {
+                root_store.add(cert).map_err(|err| {
+                    format!("Failed to add certificate from CA PEM file '{path}': {err}")
+                })?;
+            }
+
+            ClientConfig::builder().with_root_certificates(root_store)
+        }
+        TlsVerificationMode::InsecureSkipVerify => ClientConfig::builder()
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(InsecureServerCertVerifier)),
+    };
+
+    let mut config = with_client_identity(builder, login)?;
+    config.alpn_protocols = login.tls_alpn_protocols();
+    Ok(Arc::new(config))
+}
+
+fn with_client_identity(
+    builder: ConfigBuilder<ClientConfig, WantsClientCert>,
+    login: &MqttLoginData,
+) -> Result<ClientConfig, String> {
+    let Some((cert_path, key_path)) = login.tls_client_identity_paths()? else {
+        return Ok(builder.with_no_client_auth());
+    };
+
+    let certs = load_certificates(cert_path, "client certificate")?;
+    let key = load_private_key(key_path)?;
+    builder
+        .with_client_auth_cert(certs, key)
+        .map_err(|err| format!("Client certificate and key do not match: {err}"))
+}
+
+fn load_certificates(path: &str, kind: &str) -> Result<Vec<CertificateDer<'static>>, String> {
+    let file = File::open(path)
+        .map_err(|err| format!("Failed to open {kind} PEM file '{path}': {err}"))?;
+    let mut reader = BufReader::new(file);
+    let certs = rustls_pemfile::certs(&mut reader)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| format!("Failed to read {kind} PEM file '{path}': {err}"))?;
+
+    if certs.is_empty() {
+        return Err(format!(
+            "{kind} PEM file '{path}' did not contain any certificates"
+        ));
+    }
+    Ok(certs)
+}
+
+fn load_private_key(path: &str) -> Result<PrivateKeyDer<'static>, String> {
+    let file = File::open(path)
+        .map_err(|err| format!("Failed to open private key file '{path}': {err}"))?;
+    let mut reader = BufReader::new(file);
+    rustls_pemfile::private_key(&mut reader)
+        .map_err(|err| format!("Failed to read private key file '{path}': {err}"))?
+        .ok_or_else(|| format!("Private key file '{path}' did not contain a private key"))
+}
+
+fn native_root_store() -> RootCertStore {
+    let mut root_store = RootCertStore::empty();
+    let cert_result = rustls_native_certs::load_native_certs();
+    for cert in cert_result.certs {
+        let _ = root_store.add(cert);
+    }
+    root_store
+}
+
+pub(crate) async fn tls_handshake(
+    login: &MqttLoginData,
+    stream: TcpStream,
+    domain: &str,
+) -> Result<TlsStream<TcpStream>, ClientError> {
+    let tls_config = build_tls_config(login, domain).map_err(ClientError::InvalidConfig)?;
+    let server_name = ServerName::try_from(domain.to_owned()).map_err(|err| {
+        ClientError::InvalidConfig(format!("Invalid TLS server name '{domain}': {err}"))
+    })?;
+    let stream = TlsConnector::from(tls_config)
+        .connect(server_name, stream)
+        .await
+        .map_err(|err| {
+            let alert = err
+                .get_ref()
+                .and_then(|inner| inner.downcast_ref::<rustls::Error>())
+                .and_then(|tls_err| match tls_err {
+                    rustls::Error::AlertReceived(alert) => Some(*alert),
+                    _ => None,
+                });
+            match alert
+                .and_then(|alert| client_cert_rejection(alert, login.has_tls_client_identity()))
+            {
+                Some(message) => ClientError::AuthFailed(message),
+                None => ClientError::NetworkError(format!("TLS handshake failed: {err}")),
+            }
+        })?;
+
+    if let Some(expected) = login.tls_pinned_sha256_opt() {
+        let actual = peer_certificate_sha256(&stream).unwrap_or_default();
+        if actual != expected {
+            return Err(ClientError::CertificatePinMismatch { expected, actual });
+        }
+    }
+    Ok(stream)
+}
+
+fn peer_certificate_sha256(stream: &TlsStream<TcpStream>) -> Option<String> {
+    let (_, connection) = stream.get_ref();
+    let certificate = connection.peer_certificates()?.first()?;
+    Some(sha256_hex(certificate.as_ref()))
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    ring::digest::digest(&ring::digest::SHA256, data)
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+pub(crate) fn session_info(stream: &TlsStream<TcpStream>) -> TlsSessionInfo {
+    let (_, connection) = stream.get_ref();
+    let protocol_version = match connection.protocol_version() {
+        Some(ProtocolVersion::TLSv1_3) => "TLS 1.3".to_string(),
+        Some(ProtocolVersion::TLSv1_2) => "TLS 1.2".to_string(),
+        Some(other) => format!("{other:?}"),
+        None => "unknown version".to_string(),
+    };
+    let cipher_suite = connection
+        .negotiated_cipher_suite()
+        .map(|suite| format!("{:?}", suite.suite()))
+        .unwrap_or_else(|| "unknown cipher".to_string());
+    let alpn_protocol = connection
+        .alpn_protocol()
+        .map(|protocol| String::from_utf8_lossy(protocol).into_owned());
+
+    TlsSessionInfo {
+        protocol_version,
+        cipher_suite,
+        alpn_protocol,
+        certificate_sha256: peer_certificate_sha256(stream),
+    }
+}
+
+fn client_cert_rejection(alert: AlertDescription, has_client_cert: bool) -> Option<String> {
+    match alert {
+        AlertDescription::CertificateRequired if !has_client_cert => Some(
+            "Broker requires a client certificate (mutual TLS); configure a client certificate and key"
+                .to_string(),
+        ),
+        AlertDescription::BadCertificate
+        | AlertDescription::UnsupportedCertificate
+        | AlertDescription::CertificateRevoked
+        | AlertDescription::CertificateExpired
+        | AlertDescription::CertificateUnknown
+        | AlertDescription::UnknownCA
+        | AlertDescription::AccessDenied
+        | AlertDescription::CertificateRequired
+            if has_client_cert =>
+        {
+            Some(format!("Broker rejected the client certificate ({alert:?})"))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustls::AlertDescription;
+
+    use super::{client_cert_rejection, sha256_hex};
+
+    #[test]
+    fn certificate_alerts_are_reported_as_client_cert_rejections() {
+        assert_eq!(
+            client_cert_rejection(AlertDescription::BadCertificate, true).as_deref(),
+            Some("Broker rejected the client certificate (BadCertificate)")
+        );
+        assert!(
+            client_cert_rejection(AlertDescription::CertificateRequired, false)
+                .unwrap()
+                .starts_with("Broker requires a client certificate")
+        );
+        assert_eq!(
+            client_cert_rejection(AlertDescription::BadCertificate, false),
+            None
+        );
+        assert_eq!(
+            client_cert_rejection(AlertDescription::HandshakeFailure, true),
+            None
+        );
+    }
+
+    #[test]
+    fn certificate_fingerprints_are_lowercase_sha256_hex() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
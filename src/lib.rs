@@ -4,4 +4,4 @@ mod models;
 mod ui;
 mod utils;
 
-pub use app::App;
+pub use app::{App, native_options};
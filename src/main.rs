@@ -1,11 +1,24 @@
 #![cfg_attr(target_os = "windows", windows_subsystem = "windows")]
 
+const BROKER_URL_ENV: &str = "MQUI_BROKER_URL";
+
 fn main() -> eframe::Result<()> {
-    let options = eframe::NativeOptions::default();
+    let options = mqui::native_options();
+    let broker_url = std::env::args()
+        .nth(1)
+        .filter(|arg| mqui::App::is_broker_url(arg))
+        .or_else(|| std::env::var(BROKER_URL_ENV).ok())
+        .filter(|url| !url.trim().is_empty());
 
     eframe::run_native(
         "MQUI",
         options,
-        Box::new(|_cc| Ok(Box::new(mqui::App::default()))),
+        Box::new(move |cc| {
+            let mut app = mqui::App::new(cc);
+            if let Some(url) = broker_url {
+                app.open_broker_url(&url);
+            }
+            Ok(Box::new(app))
+        }),
     )
 }
@@ -0,0 +1,80 @@
+use std::collections::VecDeque;
+use std::time::SystemTime;
+
+pub(crate) const MAX_CHART_POINTS: usize = 500;
+
+#[derive(Clone, Debug)]
+pub(crate) struct TopicChart {
+    pub(crate) topic: String,
+    pub(crate) points: VecDeque<[f64; 2]>,
+    pub(crate) skipped: u64,
+    started: SystemTime,
+}
+
+impl TopicChart {
+    pub(crate) fn new(topic: String, started: SystemTime) -> Self {
+        Self {
+            topic,
+            points: VecDeque::new(),
+            skipped: 0,
+            started,
+        }
+    }
+
+    pub(crate) fn record(&mut self, payload: &[u8], at: SystemTime) {
+        let Some(value) = parse_numeric_payload(payload) else {
+            self.skipped += 1;
+            return;
+        };
+
+        let secs = at
+            .duration_since(self.started)
+            .map(|elapsed| elapsed.as_secs_f64())
+            .unwrap_or_default();
+        self.points.push_back([secs, value]);
+        while self.points.len() > MAX_CHART_POINTS {
+            let _ = self.points.pop_front();
+        }
+    }
+}
+
+pub(crate) fn parse_numeric_payload(payload: &[u8]) -> Option<f64> {
+    std::str::from_utf8(payload)
+        .ok()?
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::{MAX_CHART_POINTS, TopicChart, parse_numeric_payload};
+
+    #[test]
+    fn numeric_payloads_are_parsed_and_others_skipped() {
+        assert_eq!(parse_numeric_payload(b" 21.5\n"), Some(21.5));
+        assert_eq!(parse_numeric_payload(b"-3"), Some(-3.0));
+        assert_eq!(parse_numeric_payload(b"1e3"), Some(1000.0));
+        assert_eq!(parse_numeric_payload(b"NaN"), None);
+        assert_eq!(parse_numeric_payload(b"on"), None);
+        assert_eq!(parse_numeric_payload(&[0xff]), None);
+    }
+
+    #[test]
+    fn chart_keeps_the_most_recent_points() {
+        let start = UNIX_EPOCH + Duration::from_secs(1_000);
+        let mut chart = TopicChart::new("sensors/temp".to_string(), start);
+        chart.record(b"oops", start);
+        for index in 0..MAX_CHART_POINTS + 5 {
+            let at = start + Duration::from_millis(index as u64 * 500);
+            chart.record(index.to_string().as_bytes(), at);
+        }
+
+        assert_eq!(chart.skipped, 1);
+        assert_eq!(chart.points.len(), MAX_CHART_POINTS);
+        assert_eq!(chart.points.front(), Some(&[2.5, 5.0]));
+    }
+}
@@ -1,14 +1,191 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::mpsc::Receiver;
 
-use tokio::sync::{mpsc as tokio_mpsc, oneshot};
+use tokio::sync::{mpsc as tokio_mpsc, oneshot, watch};
 use tokio::task::JoinHandle;
 
 use crate::models::ipc::{ClientCommand, ClientEvent};
 
 #[derive(Debug)]
 pub(crate) struct ClientHandle {
-    pub(crate) shutdown_tx: Option<oneshot::Sender<()>>,
+    pub(crate) shutdown_tx: Option<oneshot::Sender<ShutdownReason>>,
     pub(crate) join_handle: JoinHandle<()>,
     pub(crate) event_rx: Receiver<ClientEvent>,
-    pub(crate) command_tx: tokio_mpsc::UnboundedSender<ClientCommand>,
+    pub(crate) command_tx: CommandSender,
+    /// Set while the window is minimised so the client holds received messages back.
+    pub(crate) ui_hidden: Arc<AtomicBool>,
+}
+
+/// Why a client task is being stopped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ShutdownReason {
+    Quit,
+    CloseTab,
+    /// The tab is about to connect again with a new client.
+    Restart,
+}
+
+impl ShutdownReason {
+    /// Whether "Send will on normal quit" applies. A restart is not the device going away, so
+    /// it always discards the will.
+    pub(crate) fn sends_will(self) -> bool {
+        !matches!(self, Self::Restart)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CommandSendError {
+    Full,
+    Closed,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct CommandSender {
+    tx: tokio_mpsc::UnboundedSender<ClientCommand>,
+    queued: Arc<AtomicUsize>,
+    dropped: Arc<AtomicU64>,
+    limit: Option<usize>,
+}
+
+#[derive(Debug)]
+pub(crate) struct CommandReceiver {
+    rx: tokio_mpsc::UnboundedReceiver<ClientCommand>,
+    queued: Arc<AtomicUsize>,
+}
+
+pub(crate) fn command_channel(limit: Option<usize>) -> (CommandSender, CommandReceiver) {
+    let (tx, rx) = tokio_mpsc::unbounded_channel();
+    let queued = Arc::new(AtomicUsize::new(0));
+    let sender = CommandSender {
+        tx,
+        queued: Arc::clone(&queued),
+        dropped: Arc::new(AtomicU64::new(0)),
+        limit: limit.map(|limit| limit.max(1)),
+    };
+    (sender, CommandReceiver { rx, queued })
+}
+
+impl CommandSender {
+    /// Sends `command`, counting it as dropped if the queue is full.
+    pub(crate) fn send(&self, command: ClientCommand) -> Result<(), CommandSendError> {
+        let result = self.try_send(command);
+        if result == Err(CommandSendError::Full) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        result
+    }
+
+    /// Like [`Self::send`], but a full queue is not counted as a drop, for callers that retry.
+    pub(crate) fn try_send(&self, command: ClientCommand) -> Result<(), CommandSendError> {
+        if self
+            .limit
+            .is_some_and(|limit| self.queued.load(Ordering::Relaxed) >= limit)
+        {
+            return Err(CommandSendError::Full);
+        }
+
+        self.queued.fetch_add(1, Ordering::Relaxed);
+        self.tx.send(command).map_err(|_| {
+            self.queued.fetch_sub(1, Ordering::Relaxed);
+            CommandSendError::Closed
+        })
+    }
+
+    pub(crate) fn queued(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl CommandReceiver {
+    pub(crate) async fn recv(&mut self) -> Option<ClientCommand> {
+        let command = self.rx.recv().await;
+        if command.is_some() {
+            self.queued.fetch_sub(1, Ordering::Relaxed);
+        }
+        command
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ReplayControl {
+    Running,
+    Paused,
+    Stopped,
+}
+
+#[derive(Debug)]
+pub(crate) struct ReplayHandle {
+    pub(crate) control_tx: watch::Sender<ReplayControl>,
+    pub(crate) sent: Arc<AtomicUsize>,
+    pub(crate) total: usize,
+    pub(crate) join_handle: JoinHandle<()>,
+}
+
+impl ReplayHandle {
+    pub(crate) fn sent(&self) -> usize {
+        self.sent.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn is_paused(&self) -> bool {
+        *self.control_tx.borrow() == ReplayControl::Paused
+    }
+
+    pub(crate) fn is_finished(&self) -> bool {
+        self.join_handle.is_finished()
+    }
+
+    pub(crate) fn set_control(&self, control: ReplayControl) {
+        let _ = self.control_tx.send(control);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CommandSendError, command_channel};
+    use crate::models::ipc::ClientCommand;
+
+    #[test]
+    fn bounded_sender_drops_and_counts_overflow() {
+        let (sender, mut receiver) = command_channel(Some(2));
+        assert_eq!(sender.send(ClientCommand::Disconnect), Ok(()));
+        assert_eq!(sender.send(ClientCommand::Disconnect), Ok(()));
+        assert_eq!(
+            sender.send(ClientCommand::Disconnect),
+            Err(CommandSendError::Full)
+        );
+        assert_eq!((sender.queued(), sender.dropped()), (2, 1));
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        assert!(runtime.block_on(receiver.recv()).is_some());
+        assert_eq!(sender.send(ClientCommand::Disconnect), Ok(()));
+
+        assert_eq!(
+            sender.try_send(ClientCommand::Disconnect),
+            Err(CommandSendError::Full)
+        );
+        assert_eq!(sender.dropped(), 1);
+    }
+
+    #[test]
+    fn unbounded_sender_never_drops() {
+        let (sender, receiver) = command_channel(None);
+        for _ in 0..1000 {
+            assert_eq!(sender.send(ClientCommand::Disconnect), Ok(()));
+        }
+        assert_eq!((sender.queued(), sender.dropped()), (1000, 0));
+
+        drop(receiver);
+        assert_eq!(
+            sender.send(ClientCommand::Disconnect),
+            Err(CommandSendError::Closed)
+        );
+        assert_eq!(sender.queued(), 1000);
+    }
 }
@@ -0,0 +1,102 @@
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ClientError {
+    AuthFailed(String),
+    NetworkError(String),
+    ProtocolError(String),
+    Timeout(String),
+    BrokerDisconnect { code: u8, reason: String },
+    SessionTakenOver { client_id: String },
+    CertificatePinMismatch { expected: String, actual: String },
+    InvalidConfig(String),
+    ClosedByUser(String),
+}
+
+impl ClientError {
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            Self::AuthFailed(_) => "Authentication failed",
+            Self::NetworkError(_) => "Network error",
+            Self::ProtocolError(_) => "Protocol error",
+            Self::Timeout(_) => "Timeout",
+            Self::BrokerDisconnect { .. } => "Disconnected by broker",
+            Self::SessionTakenOver { .. } => "Session taken over",
+            Self::CertificatePinMismatch { .. } => "Certificate pin mismatch",
+            Self::InvalidConfig(_) => "Invalid configuration",
+            Self::ClosedByUser(_) => "Disconnected",
+        }
+    }
+
+    pub(crate) fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::NetworkError(_) | Self::Timeout(_) | Self::BrokerDisconnect { .. }
+        )
+    }
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AuthFailed(message)
+            | Self::NetworkError(message)
+            | Self::ProtocolError(message)
+            | Self::Timeout(message)
+            | Self::InvalidConfig(message)
+            | Self::ClosedByUser(message) => f.write_str(message),
+            Self::BrokerDisconnect { code, reason } => {
+                write!(f, "{reason} (reason code {code:#04x})")
+            }
+            Self::SessionTakenOver { client_id } => write!(
+                f,
+                "Another connection using client ID '{client_id}' took over this session \
+                 (reason code 0x8e); give each connection a unique client ID"
+            ),
+            Self::CertificatePinMismatch { expected, actual } => write!(
+                f,
+                "Broker certificate SHA-256 {actual} does not match the pinned {expected}; \
+                 if the certificate was rotated on purpose, clear the pin in the connection settings"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ClientError;
+
+    #[test]
+    fn only_transient_failures_are_retryable() {
+        assert!(ClientError::NetworkError("reset".to_string()).is_retryable());
+        assert!(ClientError::Timeout("keep-alive".to_string()).is_retryable());
+        assert!(
+            ClientError::BrokerDisconnect {
+                code: 0x8b,
+                reason: "ServerShuttingDown".to_string(),
+            }
+            .is_retryable()
+        );
+        assert!(!ClientError::AuthFailed("bad password".to_string()).is_retryable());
+        assert!(!ClientError::InvalidConfig("no host".to_string()).is_retryable());
+        assert!(!ClientError::ClosedByUser("bye".to_string()).is_retryable());
+        assert!(
+            !ClientError::SessionTakenOver {
+                client_id: "sensor".to_string(),
+            }
+            .is_retryable()
+        );
+    }
+
+    #[test]
+    fn broker_disconnect_display_includes_code() {
+        let err = ClientError::BrokerDisconnect {
+            code: 0x8e,
+            reason: "Broker disconnected: SessionTakenOver".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Broker disconnected: SessionTakenOver (reason code 0x8e)"
+        );
+    }
+}
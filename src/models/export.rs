@@ -0,0 +1,343 @@
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::ipc::ClientCommand;
+use crate::models::mqtt::{MessageProperties, ReceivedMessage};
+use crate::utils::formatting::{decode_base64, encode_base64};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ExportedMessage {
+    pub(crate) timestamp_ms: u64,
+    pub(crate) topic: String,
+    pub(crate) qos: u8,
+    pub(crate) retain: bool,
+    #[serde(default)]
+    pub(crate) properties: ExportedProperties,
+    pub(crate) payload_base64: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ExportedProperties {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) message_expiry_secs: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) content_type: Option<String>,
+    #[serde(default)]
+    pub(crate) payload_is_utf8: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) response_topic: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) correlation_data_base64: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) user_properties: Vec<(String, String)>,
+}
+
+impl ExportedMessage {
+    pub(crate) fn from_message(msg: &ReceivedMessage) -> Self {
+        let properties = &msg.properties;
+        Self {
+            timestamp_ms: msg
+                .timestamp
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_millis() as u64)
+                .unwrap_or_default(),
+            topic: msg.topic.clone(),
+            qos: msg.qos,
+            retain: msg.retain,
+            properties: ExportedProperties {
+                message_expiry_secs: properties.message_expiry_secs,
+                content_type: properties.content_type.clone(),
+                payload_is_utf8: properties.payload_is_utf8,
+                response_topic: properties.response_topic.clone(),
+                correlation_data_base64: properties.correlation_data.as_deref().map(encode_base64),
+                user_properties: properties.user_properties.clone(),
+            },
+            payload_base64: encode_base64(&msg.payload),
+        }
+    }
+
+    pub(crate) fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|err| format!("Failed to serialize message: {err}"))
+    }
+
+    pub(crate) fn to_publish_command(&self) -> Result<ClientCommand, String> {
+        let payload = decode_base64(&self.payload_base64)
+            .map_err(|err| format!("Message on '{}' has an {err}", self.topic))?;
+        let correlation_data = self
+            .properties
+            .correlation_data_base64
+            .as_deref()
+            .map(decode_base64)
+            .transpose()
+            .map_err(|err| format!("Correlation data on '{}' has an {err}", self.topic))?;
+
+        Ok(ClientCommand::Publish {
+            topic: self.topic.clone(),
+            payload,
+            qos: self.qos,
+            retain: self.retain,
+            properties: MessageProperties {
+                message_expiry_secs: self.properties.message_expiry_secs,
+                content_type: self.properties.content_type.clone(),
+                payload_is_utf8: self.properties.payload_is_utf8,
+                response_topic: self.properties.response_topic.clone(),
+                correlation_data,
+                user_properties: self.properties.user_properties.clone(),
+            },
+        })
+    }
+}
+
+/// Reads a JSON array, a single JSON object, JSON Lines, or CSV (see [`parse_csv_log`]).
+pub(crate) fn parse_message_log(text: &str) -> Result<Vec<ExportedMessage>, String> {
+    let trimmed = text.trim_start();
+    if trimmed.starts_with('[') {
+        return serde_json::from_str(trimmed).map_err(|err| format!("Invalid message log: {err}"));
+    }
+    if !trimmed.starts_with('{') {
+        return parse_csv_log(text);
+    }
+    if let Ok(message) = serde_json::from_str(trimmed) {
+        return Ok(vec![message]);
+    }
+
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line)
+                .map_err(|err| format!("Invalid message on line {}: {err}", index + 1))
+        })
+        .collect()
+}
+
+/// A CSV log with a header row. `topic` and one of `payload_base64` or `payload` (plain text)
+/// are required; `timestamp_ms`, `qos`, `retain`, `content_type` and `response_topic` are
+/// optional and default like a JSON entry without them.
+fn parse_csv_log(text: &str) -> Result<Vec<ExportedMessage>, String> {
+    let mut records = csv_records(text)?.into_iter();
+    let Some((_, header)) = records.next() else {
+        return Ok(Vec::new());
+    };
+    let column = |name: &str| header.iter().position(|title| title.trim() == name);
+    let topic = column("topic").ok_or("CSV message log has no topic column")?;
+    let payload_base64 = column("payload_base64");
+    let payload = column("payload");
+    if payload_base64.is_none() && payload.is_none() {
+        return Err("CSV message log has no payload_base64 or payload column".to_string());
+    }
+    let timestamp_ms = column("timestamp_ms");
+    let qos = column("qos");
+    let retain = column("retain");
+    let content_type = column("content_type");
+    let response_topic = column("response_topic");
+
+    records
+        .map(|(line, fields)| {
+            let field = |index: Option<usize>| {
+                index
+                    .and_then(|index| fields.get(index))
+                    .map(String::as_str)
+                    .filter(|value| !value.is_empty())
+            };
+            let invalid =
+                |name: &str, value: &str| format!("Invalid {name} '{value}' on line {line}");
+
+            Ok(ExportedMessage {
+                timestamp_ms: field(timestamp_ms)
+                    .map(|value| value.parse().map_err(|_| invalid("timestamp_ms", value)))
+                    .transpose()?
+                    .unwrap_or_default(),
+                topic: field(Some(topic))
+                    .ok_or_else(|| format!("Missing topic on line {line}"))?
+                    .to_string(),
+                qos: field(qos)
+                    .map(|value| value.parse().map_err(|_| invalid("qos", value)))
+                    .transpose()?
+                    .unwrap_or_default(),
+                retain: match field(retain) {
+                    None | Some("false" | "0") => false,
+                    Some("true" | "1") => true,
+                    Some(value) => return Err(invalid("retain", value)),
+                },
+                properties: ExportedProperties {
+                    content_type: field(content_type).map(str::to_string),
+                    response_topic: field(response_topic).map(str::to_string),
+                    ..Default::default()
+                },
+                payload_base64: match field(payload_base64) {
+                    Some(value) => value.to_string(),
+                    None => encode_base64(field(payload).unwrap_or_default().as_bytes()),
+                },
+            })
+        })
+        .collect()
+}
+
+/// Splits CSV text into records with the line each starts on. Quoted fields may contain
+/// commas, newlines and doubled quotes; blank lines are skipped.
+fn csv_records(text: &str) -> Result<Vec<(usize, Vec<String>)>, String> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut start_line = 1;
+
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if quoted => {
+                if chars.next_if_eq(&'"').is_some() {
+                    field.push('"');
+                } else {
+                    quoted = false;
+                }
+            }
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+            '\n' if !quoted => {
+                fields.push(std::mem::take(&mut field));
+                let record = std::mem::take(&mut fields);
+                if record.iter().any(|field| !field.is_empty()) {
+                    records.push((start_line, record));
+                }
+                line += 1;
+                start_line = line;
+            }
+            _ => {
+                if ch == '\n' {
+                    line += 1;
+                }
+                field.push(ch);
+            }
+        }
+    }
+    if quoted {
+        return Err(format!("Unterminated quoted field on line {start_line}"));
+    }
+    fields.push(field);
+    if fields.iter().any(|field| !field.is_empty()) {
+        records.push((start_line, fields));
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::{ExportedMessage, parse_message_log};
+    use crate::models::ipc::ClientCommand;
+    use crate::models::mqtt::{MessageProperties, ReceivedMessage};
+
+    fn sample_message() -> ReceivedMessage {
+        ReceivedMessage {
+            timestamp: UNIX_EPOCH + Duration::from_millis(1_700_000_000_123),
+            qos: 1,
+            retain: true,
+            properties: MessageProperties {
+                content_type: Some("text/plain".to_string()),
+                correlation_data: Some(b"id".to_vec()),
+                user_properties: vec![("ts".to_string(), "1700000000000".to_string())],
+                ..Default::default()
+            },
+            ..ReceivedMessage::for_test("sensors/temp", "21.5")
+        }
+    }
+
+    #[test]
+    fn exported_json_carries_metadata_and_base64_payload() {
+        let msg = sample_message();
+        let json: serde_json::Value =
+            serde_json::from_str(&ExportedMessage::from_message(&msg).to_json().unwrap()).unwrap();
+        assert_eq!(json["timestamp_ms"], 1_700_000_000_123u64);
+        assert_eq!(json["topic"], "sensors/temp");
+        assert_eq!(json["qos"], 1);
+        assert_eq!(json["retain"], true);
+        assert_eq!(json["payload_base64"], "MjEuNQ==");
+        assert_eq!(json["properties"]["content_type"], "text/plain");
+        assert_eq!(json["properties"]["correlation_data_base64"], "aWQ=");
+        assert_eq!(
+            json["properties"]["user_properties"],
+            serde_json::json!([["ts", "1700000000000"]])
+        );
+        assert!(json["properties"].get("response_topic").is_none());
+    }
+
+    #[test]
+    fn message_logs_parse_as_array_object_or_json_lines() {
+        let exported = ExportedMessage::from_message(&sample_message());
+        let single = exported.to_json().unwrap();
+        let line = serde_json::to_string(&exported).unwrap();
+
+        assert_eq!(parse_message_log(&single).unwrap(), [exported]);
+        assert_eq!(
+            parse_message_log(&format!("[{line},{line}]"))
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(
+            parse_message_log(&format!("{line}\n\n{line}\n"))
+                .unwrap()
+                .len(),
+            2
+        );
+        assert!(
+            parse_message_log(&format!("{line}\nnot json"))
+                .unwrap_err()
+                .contains("line 2")
+        );
+    }
+
+    #[test]
+    fn csv_logs_parse_by_header_name() {
+        let csv = "topic,qos,retain,payload,timestamp_ms\r\n\
+                   sensors/temp,1,true,21.5,1700000000123\r\n\
+                   \r\n\
+                   \"a,b\",0,false,\"say \"\"hi\"\"\nbye\",\n";
+        let messages = parse_message_log(csv).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].topic, "sensors/temp");
+        assert_eq!((messages[0].qos, messages[0].retain), (1, true));
+        assert_eq!(messages[0].timestamp_ms, 1_700_000_000_123);
+        assert_eq!(messages[0].payload_base64, "MjEuNQ==");
+        assert_eq!(messages[1].topic, "a,b");
+        assert_eq!(messages[1].timestamp_ms, 0);
+        let ClientCommand::Publish { payload, .. } = messages[1].to_publish_command().unwrap()
+        else {
+            panic!("expected a publish command");
+        };
+        assert_eq!(payload, b"say \"hi\"\nbye");
+
+        assert!(
+            parse_message_log("topic,payload_base64,qos\nt,,x")
+                .unwrap_err()
+                .contains("line 2")
+        );
+        assert!(parse_message_log("qos,payload\n0,x").is_err());
+    }
+
+    #[test]
+    fn exported_message_converts_back_to_publish() {
+        let exported = ExportedMessage::from_message(&sample_message());
+        let Ok(ClientCommand::Publish {
+            topic,
+            payload,
+            qos,
+            retain,
+            properties,
+        }) = exported.to_publish_command()
+        else {
+            panic!("expected a publish command");
+        };
+        assert_eq!(topic, "sensors/temp");
+        assert_eq!(payload, b"21.5");
+        assert_eq!((qos, retain), (1, true));
+        assert_eq!(properties, sample_message().properties);
+    }
+}
@@ -1,31 +1,75 @@
+use std::time::Duration;
+
+use crate::models::error::ClientError;
+use crate::models::mqtt::{AckMode, MessageProperties, ReceivedMessage, TlsSessionInfo};
+use crate::models::stats::RttSummary;
+
+/// Messages dropped on one topic from a backlog held back while the window was hidden.
+#[derive(Debug)]
+pub(crate) struct SkippedTopic {
+    pub(crate) count: u64,
+    pub(crate) bytes: u64,
+    /// The newest of the dropped messages.
+    pub(crate) latest: ReceivedMessage,
+}
+
 #[derive(Debug)]
 pub(crate) enum ClientEvent {
     Status(String),
-    Error(String),
+    Error(ClientError),
+    Log(String),
+    /// Summary of a packet written to the broker, for the outbound packet log.
+    PacketSent(String),
     Connected,
-    Disconnected(String),
+    Disconnected(ClientError),
+    KeepAlive {
+        secs: u16,
+        from_server: bool,
+    },
+    PingResponse,
+    AssignedClientId(String),
+    TlsSession(TlsSessionInfo),
+    MaximumQos(u8),
     Subscribed {
         topic: String,
         qos: u8,
+        granted_qos: u8,
+        packet_id: u16,
         details: String,
+        /// Time from sending the SUBSCRIBE to receiving its SUBACK.
+        ack_elapsed: Duration,
+    },
+    /// The broker rejected one topic filter of a SUBSCRIBE.
+    SubscribeFailed {
+        topic: String,
+        packet_id: u16,
+        reason: String,
     },
     Unsubscribed {
         topic: String,
+        packet_id: u16,
         details: String,
     },
     Published {
         topic: String,
         packet_id: Option<u16>,
+        request: Option<u64>,
+        /// Time from sending the PUBLISH to its PUBACK or PUBCOMP; `None` for QoS 0.
+        ack_elapsed: Option<Duration>,
     },
-    MessageReceived {
-        topic: String,
-        qos: u8,
-        retain: bool,
-        payload: Vec<u8>,
+    /// Messages received within one batching window, oldest first.
+    MessageBatch(Vec<ReceivedMessage>),
+    /// Messages dropped from the backlog held back while the window was hidden, tallied per
+    /// topic and ordered by their newest message, oldest first.
+    MessagesSkipped(Vec<SkippedTopic>),
+    Acked {
+        packet_id: u16,
     },
+    RttSample(Duration),
+    RttFinished(RttSummary),
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub(crate) enum ClientCommand {
     Disconnect,
     ForceDisconnect,
@@ -33,6 +77,10 @@ pub(crate) enum ClientCommand {
         topic: String,
         qos: u8,
     },
+    /// Subscribes to several filters in one SUBSCRIBE packet.
+    SubscribeMany {
+        entries: Vec<(String, u8)>,
+    },
     Unsubscribe {
         topic: String,
     },
@@ -41,5 +89,16 @@ pub(crate) enum ClientCommand {
         payload: Vec<u8>,
         qos: u8,
         retain: bool,
+        properties: MessageProperties,
+    },
+    SetAckMode {
+        mode: AckMode,
+        delay: Duration,
+    },
+    Ack {
+        packet_id: u16,
+    },
+    MeasureRtt {
+        count: u32,
     },
 }
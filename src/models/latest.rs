@@ -0,0 +1,152 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::models::mqtt::ReceivedMessage;
+
+/// Most topics whose latest message is kept. Past this the least recently updated topic is
+/// dropped.
+pub(crate) const MAX_LATEST_TOPICS: usize = 10_000;
+
+/// One entry of the topic tree, built from [`LatestByTopic::children`].
+#[derive(Debug)]
+pub(crate) struct TopicNode<'a> {
+    /// The topic level, or the whole rest of the topic where the depth limit folds it.
+    pub(crate) label: String,
+    pub(crate) path: String,
+    /// Latest message published exactly on `path`, if any.
+    pub(crate) message: Option<&'a ReceivedMessage>,
+    /// Topics below this node; zero for a leaf.
+    pub(crate) descendants: usize,
+}
+
+/// The newest message seen on each topic. Unlike the tab's message buffer this is never
+/// trimmed by message count, so a rarely updated topic keeps its value however busy the
+/// other topics are.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct LatestByTopic {
+    latest: HashMap<String, ReceivedMessage>,
+}
+
+impl LatestByTopic {
+    pub(crate) fn record(&mut self, message: &ReceivedMessage) {
+        if let Some(latest) = self.latest.get_mut(&message.topic) {
+            *latest = message.clone();
+            return;
+        }
+
+        if self.latest.len() >= MAX_LATEST_TOPICS
+            && let Some(stalest) = self
+                .latest
+                .values()
+                .min_by_key(|latest| latest.timestamp)
+                .map(|latest| latest.topic.clone())
+        {
+            let _ = self.latest.remove(&stalest);
+        }
+        let _ = self.latest.insert(message.topic.clone(), message.clone());
+    }
+
+    pub(crate) fn get(&self, topic: &str) -> Option<&ReceivedMessage> {
+        self.latest.get(topic)
+    }
+
+    /// The tree level directly below `parent` (`None` for the top), sorted by label. Levels are
+    /// built only when asked for, so collapsed branches cost nothing. At `max_depth` the rest
+    /// of each topic is folded into a single leaf.
+    pub(crate) fn children(
+        &self,
+        parent: Option<&str>,
+        max_depth: Option<usize>,
+    ) -> Vec<TopicNode<'_>> {
+        let depth = parent.map_or(0, |parent| parent.split('/').count());
+        let fold = max_depth.is_some_and(|max| depth + 1 >= max);
+
+        let mut nodes: BTreeMap<&str, TopicNode<'_>> = BTreeMap::new();
+        for (topic, message) in &self.latest {
+            let rest = match parent {
+                None => topic.as_str(),
+                Some(parent) => match topic
+                    .strip_prefix(parent)
+                    .and_then(|rest| rest.strip_prefix('/'))
+                {
+                    Some(rest) => rest,
+                    None => continue,
+                },
+            };
+            let (label, below) = match rest.split_once('/') {
+                Some((level, _)) if !fold => (level, true),
+                _ => (rest, false),
+            };
+            let node = nodes.entry(label).or_insert_with(|| TopicNode {
+                label: label.to_string(),
+                path: match parent {
+                    Some(parent) => format!("{parent}/{label}"),
+                    None => label.to_string(),
+                },
+                message: None,
+                descendants: 0,
+            });
+            if below {
+                node.descendants += 1;
+            } else {
+                node.message = Some(message);
+            }
+        }
+        nodes.into_values().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use super::{LatestByTopic, MAX_LATEST_TOPICS};
+    use crate::models::mqtt::ReceivedMessage;
+
+    fn message(topic: &str, payload: &str, secs: u64) -> ReceivedMessage {
+        ReceivedMessage {
+            timestamp: SystemTime::UNIX_EPOCH + Duration::from_secs(secs),
+            ..ReceivedMessage::for_test(topic, payload)
+        }
+    }
+
+    #[test]
+    fn tree_levels_are_built_per_parent_and_folded_at_the_depth_limit() {
+        let mut latest = LatestByTopic::default();
+        for topic in ["a", "a/b", "a/b/c/d", "a/x", "z"] {
+            latest.record(&message(topic, topic, 0));
+        }
+
+        let top = latest.children(None, None);
+        let labels: Vec<_> = top.iter().map(|node| node.label.as_str()).collect();
+        assert_eq!(labels, ["a", "z"]);
+        assert_eq!(top[0].descendants, 3);
+        assert!(top[0].message.is_some());
+
+        let below_a = latest.children(Some("a"), None);
+        let paths: Vec<_> = below_a.iter().map(|node| node.path.as_str()).collect();
+        assert_eq!(paths, ["a/b", "a/x"]);
+        assert_eq!(below_a[0].descendants, 1);
+
+        let folded = latest.children(Some("a"), Some(2));
+        let labels: Vec<_> = folded.iter().map(|node| node.label.as_str()).collect();
+        assert_eq!(labels, ["b", "b/c/d", "x"]);
+        assert!(folded.iter().all(|node| node.descendants == 0));
+    }
+
+    #[test]
+    fn keeps_the_newest_value_per_topic_and_drops_the_stalest_topic() {
+        let mut latest = LatestByTopic::default();
+        latest.record(&message("rare", "1", 0));
+        latest.record(&message("busy", "1", 1));
+        latest.record(&message("busy", "2", 2));
+        assert_eq!(latest.get("busy").unwrap().payload, b"2");
+
+        latest.record(&message("rare", "2", 3));
+        for index in 0..MAX_LATEST_TOPICS - 1 {
+            latest.record(&message(&index.to_string(), "x", 4));
+        }
+        assert!(latest.get("0").is_some());
+        assert!(latest.get("busy").is_none());
+        assert_eq!(latest.get("rare").unwrap().payload, b"2");
+    }
+}
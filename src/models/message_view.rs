@@ -0,0 +1,464 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::mqtt::{MAX_STORED_MESSAGES, ReceivedMessage};
+use crate::models::protobuf::{ProtobufMapping, ProtobufSchema, message_type_for};
+use crate::utils::formatting::{
+    PayloadDecoder, PayloadEncoding, decode_payload, format_payload, format_timestamp,
+};
+use crate::utils::json_path::{extract_columns, parse_json_path};
+use crate::utils::topic::topic_matches;
+
+/// How often the Messages panel picks up newly received messages.
+pub(crate) const MESSAGE_VIEW_REFRESH: Duration = Duration::from_millis(100);
+/// How long a cleared message buffer can still be restored.
+pub(crate) const CLEAR_UNDO_WINDOW: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ViewKey {
+    filter: String,
+    muted: Vec<String>,
+    collapse: bool,
+    max_rows: usize,
+    columns: Vec<String>,
+    flags: FlagFilter,
+    encoding: PayloadEncoding,
+    decoder: PayloadDecoder,
+    protobuf_mappings: Vec<ProtobufMapping>,
+}
+
+/// Which QoS levels and retain flags the Messages panel shows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct FlagFilter {
+    pub(crate) qos: [bool; 3],
+    pub(crate) retained: bool,
+    pub(crate) not_retained: bool,
+}
+
+impl Default for FlagFilter {
+    fn default() -> Self {
+        Self {
+            qos: [true; 3],
+            retained: true,
+            not_retained: true,
+        }
+    }
+}
+
+impl FlagFilter {
+    pub(crate) fn matches(&self, msg: &ReceivedMessage) -> bool {
+        let qos = self.qos.get(usize::from(msg.qos)).copied().unwrap_or(true);
+        qos && if msg.retain {
+            self.retained
+        } else {
+            self.not_retained
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct MessageRow {
+    pub(crate) message: ReceivedMessage,
+    pub(crate) repeats: usize,
+    /// Values of the tab's JSON path columns, blank where a path does not match.
+    pub(crate) columns: Vec<String>,
+    /// The payload as displayed, decoded once here rather than on every repaint.
+    pub(crate) payload_text: String,
+    pub(crate) protobuf_error: Option<String>,
+}
+
+/// Filtered, newest-first snapshot of a tab's messages. Filter changes rebuild it right away;
+/// new messages are folded in at most every [`MESSAGE_VIEW_REFRESH`] so a flood of messages
+/// does not re-filter the whole buffer on every repaint.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct MessageView {
+    rows: Vec<MessageRow>,
+    key: Option<ViewKey>,
+    received: u64,
+    built_at: Option<Instant>,
+}
+
+pub(crate) struct ViewFilter<'a> {
+    pub(crate) filter: &'a str,
+    pub(crate) muted: &'a [String],
+    pub(crate) collapse: bool,
+    pub(crate) max_rows: usize,
+    pub(crate) columns: &'a [String],
+    pub(crate) flags: FlagFilter,
+    pub(crate) encoding: PayloadEncoding,
+    pub(crate) decoder: PayloadDecoder,
+    /// Loaded schema and topic mappings; call [`MessageView::invalidate`] when the schema
+    /// is replaced.
+    pub(crate) protobuf: Option<(&'a ProtobufSchema, &'a [ProtobufMapping])>,
+}
+
+impl MessageView {
+    pub(crate) fn rows(&self) -> &[MessageRow] {
+        &self.rows
+    }
+
+    /// The displayed rows as plain text, one `timestamp topic payload` line each, for pasting
+    /// into chat or a ticket. Line breaks inside payloads are escaped to keep one row per line.
+    pub(crate) fn copy_text(&self, encoding: PayloadEncoding) -> String {
+        let mut text = String::new();
+        for row in &self.rows {
+            let msg = &row.message;
+            let payload = format_payload(&msg.payload, encoding)
+                .replace('\r', "\\r")
+                .replace('\n', "\\n");
+            text.push_str(&format!(
+                "{} {} {payload}",
+                format_timestamp(msg.timestamp),
+                msg.topic
+            ));
+            if row.repeats > 1 {
+                text.push_str(&format!(" (×{})", row.repeats));
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Forces a rebuild on the next refresh, for changes other than newly received messages.
+    pub(crate) fn invalidate(&mut self) {
+        self.key = None;
+    }
+
+    /// Rebuilds the snapshot if it is out of date. `received` is the tab's running receive
+    /// count. Returns how long until messages held back by the throttle become visible.
+    pub(crate) fn refresh(
+        &mut self,
+        messages: &VecDeque<ReceivedMessage>,
+        received: u64,
+        filter: &ViewFilter<'_>,
+        now: Instant,
+    ) -> Option<Duration> {
+        let key = ViewKey {
+            filter: filter.filter.trim().to_string(),
+            muted: filter.muted.to_vec(),
+            collapse: filter.collapse,
+            max_rows: filter.max_rows,
+            columns: filter.columns.to_vec(),
+            flags: filter.flags,
+            encoding: filter.encoding,
+            decoder: filter.decoder,
+            protobuf_mappings: filter
+                .protobuf
+                .map(|(_, mappings)| mappings.to_vec())
+                .unwrap_or_default(),
+        };
+        if self.key.as_ref() == Some(&key) {
+            if self.received == received {
+                return None;
+            }
+            let elapsed = self
+                .built_at
+                .map(|built_at| now.saturating_duration_since(built_at))
+                .unwrap_or(MESSAGE_VIEW_REFRESH);
+            if elapsed < MESSAGE_VIEW_REFRESH {
+                return Some(MESSAGE_VIEW_REFRESH - elapsed);
+            }
+        }
+
+        let visible = messages.iter().rev().filter(|msg| {
+            (key.filter.is_empty() || msg.topic.contains(&key.filter))
+                && key.flags.matches(msg)
+                && !key
+                    .muted
+                    .iter()
+                    .any(|pattern| topic_matches(pattern, &msg.topic))
+        });
+        let rows = if key.collapse {
+            collapse_repeats(visible)
+        } else {
+            visible.map(|msg| (msg, 1)).collect()
+        };
+        let paths: Vec<_> = key
+            .columns
+            .iter()
+            .map(|path| parse_json_path(path).unwrap_or_default())
+            .collect();
+        self.rows = rows
+            .into_iter()
+            .take(key.max_rows)
+            .map(|(msg, repeats)| {
+                let (payload_text, protobuf_error) = payload_text(msg, filter);
+                MessageRow {
+                    message: msg.clone(),
+                    repeats,
+                    columns: if paths.is_empty() {
+                        Vec::new()
+                    } else {
+                        extract_columns(&msg.payload, &paths)
+                    },
+                    payload_text,
+                    protobuf_error,
+                }
+            })
+            .collect();
+        self.key = Some(key);
+        self.received = received;
+        self.built_at = Some(now);
+        None
+    }
+}
+
+/// The payload as the Messages panel shows it: protobuf if the topic is mapped, then the
+/// chosen decoder, then the plain encoding. Also returns why protobuf decoding failed.
+fn payload_text(msg: &ReceivedMessage, filter: &ViewFilter<'_>) -> (String, Option<String>) {
+    let protobuf = filter.protobuf.and_then(|(schema, mappings)| {
+        message_type_for(mappings, &msg.topic)
+            .map(|message_type| schema.decode(message_type, &msg.payload))
+    });
+    let (protobuf_text, protobuf_error) = match protobuf {
+        Some(Ok(text)) => (Some(text), None),
+        Some(Err(err)) => (None, Some(err)),
+        None => (None, None),
+    };
+    let text = protobuf_text
+        .or_else(|| {
+            (filter.encoding != PayloadEncoding::Hex)
+                .then(|| {
+                    decode_payload(
+                        &msg.payload,
+                        filter.decoder,
+                        msg.properties.content_mime().as_deref(),
+                    )
+                })
+                .flatten()
+        })
+        .unwrap_or_else(|| format_payload(&msg.payload, filter.encoding));
+    (text, protobuf_error)
+}
+
+/// Messages removed by Clear, set aside so the clear can be undone for a few seconds.
+#[derive(Clone, Debug)]
+pub(crate) struct ClearedMessages {
+    messages: VecDeque<ReceivedMessage>,
+    cleared_at: Instant,
+}
+
+impl ClearedMessages {
+    pub(crate) fn take(messages: &mut VecDeque<ReceivedMessage>, now: Instant) -> Self {
+        Self {
+            messages: std::mem::take(messages),
+            cleared_at: now,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Time left to undo, or `None` once the window has passed.
+    pub(crate) fn remaining(&self, now: Instant) -> Option<Duration> {
+        CLEAR_UNDO_WINDOW.checked_sub(now.saturating_duration_since(self.cleared_at))
+    }
+
+    /// Puts the cleared messages back in front of anything received since, dropping the
+    /// oldest if the buffer would overflow.
+    pub(crate) fn restore(mut self, messages: &mut VecDeque<ReceivedMessage>) {
+        self.messages.append(messages);
+        while self.messages.len() > MAX_STORED_MESSAGES {
+            let _ = self.messages.pop_front();
+        }
+        *messages = self.messages;
+    }
+}
+
+pub(crate) fn collapse_repeats<'a>(
+    messages: impl IntoIterator<Item = &'a ReceivedMessage>,
+) -> Vec<(&'a ReceivedMessage, usize)> {
+    let mut rows: Vec<(&ReceivedMessage, usize)> = Vec::new();
+    for msg in messages {
+        match rows.last_mut() {
+            Some((previous, count))
+                if previous.topic == msg.topic
+                    && previous.payload == msg.payload
+                    && !previous.ack_pending
+                    && !msg.ack_pending =>
+            {
+                *count += 1;
+            }
+            _ => rows.push((msg, 1)),
+        }
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::time::{Duration, Instant, SystemTime};
+
+    use super::{
+        CLEAR_UNDO_WINDOW, ClearedMessages, FlagFilter, MESSAGE_VIEW_REFRESH, MessageView,
+        ViewFilter, collapse_repeats,
+    };
+    use crate::models::mqtt::ReceivedMessage;
+    use crate::utils::formatting::{PayloadDecoder, PayloadEncoding, format_payload};
+
+    #[test]
+    fn only_consecutive_identical_messages_collapse() {
+        let messages = [
+            ReceivedMessage::for_test("a", "1"),
+            ReceivedMessage::for_test("a", "1"),
+            ReceivedMessage::for_test("a", "2"),
+            ReceivedMessage::for_test("b", "2"),
+            ReceivedMessage::for_test("a", "2"),
+            ReceivedMessage::for_test("a", "2"),
+            ReceivedMessage::for_test("a", "2"),
+        ];
+
+        let counts: Vec<_> = collapse_repeats(&messages)
+            .into_iter()
+            .map(|(msg, count)| (msg.topic.as_str(), count))
+            .collect();
+        assert_eq!(counts, [("a", 2), ("a", 1), ("b", 1), ("a", 3)]);
+    }
+
+    #[test]
+    fn new_messages_are_throttled_but_filter_changes_are_not() {
+        let mut messages = VecDeque::from([ReceivedMessage::for_test("a", "1")]);
+        let mut view = MessageView::default();
+        let all = ViewFilter {
+            filter: "",
+            muted: &[],
+            collapse: false,
+            max_rows: 10,
+            columns: &[],
+            flags: FlagFilter::default(),
+            encoding: PayloadEncoding::Utf8,
+            decoder: PayloadDecoder::default(),
+            protobuf: None,
+        };
+        let start = Instant::now();
+        assert_eq!(view.refresh(&messages, 1, &all, start), None);
+        assert_eq!(view.rows().len(), 1);
+
+        messages.push_back(ReceivedMessage::for_test("b", "2"));
+        assert!(view.refresh(&messages, 2, &all, start).is_some());
+        assert_eq!(view.rows().len(), 1);
+
+        let only_b = ViewFilter { filter: "b", ..all };
+        assert_eq!(view.refresh(&messages, 2, &only_b, start), None);
+        assert_eq!(view.rows()[0].message.topic, "b");
+
+        messages.push_back(ReceivedMessage::for_test("b", "3"));
+        let later = start + MESSAGE_VIEW_REFRESH;
+        assert_eq!(view.refresh(&messages, 3, &only_b, later), None);
+        assert_eq!(view.rows().len(), 2);
+        assert_eq!(view.rows()[0].message.payload, b"3");
+        assert_eq!(view.rows()[0].payload_text, "3");
+
+        let hex = ViewFilter {
+            encoding: PayloadEncoding::Hex,
+            ..only_b
+        };
+        assert_eq!(view.refresh(&messages, 3, &hex, later), None);
+        assert_eq!(
+            view.rows()[0].payload_text,
+            format_payload(b"3", PayloadEncoding::Hex)
+        );
+    }
+
+    #[test]
+    fn flag_filter_hides_by_qos_and_retain() {
+        let mut retained = ReceivedMessage::for_test("a", "1");
+        retained.retain = true;
+        let mut qos2 = ReceivedMessage::for_test("b", "2");
+        qos2.qos = 2;
+        let messages = VecDeque::from([retained, qos2, ReceivedMessage::for_test("c", "3")]);
+
+        let only_retained = FlagFilter {
+            not_retained: false,
+            ..FlagFilter::default()
+        };
+        let mut view = MessageView::default();
+        let filter = ViewFilter {
+            filter: "",
+            muted: &[],
+            collapse: false,
+            max_rows: 10,
+            columns: &[],
+            flags: only_retained,
+            encoding: PayloadEncoding::Utf8,
+            decoder: PayloadDecoder::default(),
+            protobuf: None,
+        };
+        view.refresh(&messages, 3, &filter, Instant::now());
+        let topics: Vec<_> = view
+            .rows()
+            .iter()
+            .map(|row| row.message.topic.as_str())
+            .collect();
+        assert_eq!(topics, ["a"]);
+
+        let no_qos0 = FlagFilter {
+            qos: [false, true, true],
+            ..FlagFilter::default()
+        };
+        let filter = ViewFilter {
+            flags: no_qos0,
+            ..filter
+        };
+        view.refresh(&messages, 3, &filter, Instant::now());
+        let topics: Vec<_> = view
+            .rows()
+            .iter()
+            .map(|row| row.message.topic.as_str())
+            .collect();
+        assert_eq!(topics, ["b"]);
+    }
+
+    #[test]
+    fn copied_text_has_one_line_per_visible_row() {
+        let mut messages = VecDeque::from([
+            ReceivedMessage::for_test("a", "one\ntwo"),
+            ReceivedMessage::for_test("b", "hidden"),
+            ReceivedMessage::for_test("a", "3"),
+        ]);
+        messages[0].timestamp = SystemTime::UNIX_EPOCH + Duration::from_secs(10);
+        messages[2].timestamp = SystemTime::UNIX_EPOCH + Duration::from_secs(20);
+        let mut view = MessageView::default();
+        let filter = ViewFilter {
+            filter: "a",
+            muted: &[],
+            collapse: false,
+            max_rows: 10,
+            columns: &[],
+            flags: FlagFilter::default(),
+            encoding: PayloadEncoding::Utf8,
+            decoder: PayloadDecoder::default(),
+            protobuf: None,
+        };
+        view.refresh(&messages, 3, &filter, Instant::now());
+
+        assert_eq!(
+            view.copy_text(PayloadEncoding::Utf8),
+            "20 a 3\n10 a one\\ntwo\n"
+        );
+    }
+
+    #[test]
+    fn undoing_a_clear_keeps_messages_received_since() {
+        let start = Instant::now();
+        let mut messages = VecDeque::from([
+            ReceivedMessage::for_test("a", "1"),
+            ReceivedMessage::for_test("a", "2"),
+        ]);
+        let cleared = ClearedMessages::take(&mut messages, start);
+        assert!(messages.is_empty());
+        assert_eq!(cleared.len(), 2);
+        assert!(cleared.remaining(start + CLEAR_UNDO_WINDOW / 2).is_some());
+        assert!(cleared.remaining(start + CLEAR_UNDO_WINDOW * 2).is_none());
+
+        messages.push_back(ReceivedMessage::for_test("a", "3"));
+        cleared.restore(&mut messages);
+        let payloads: Vec<_> = messages.iter().map(|msg| msg.payload.clone()).collect();
+        assert_eq!(payloads, [b"1", b"2", b"3"]);
+    }
+}
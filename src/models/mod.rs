@@ -1,3 +1,13 @@
+pub(crate) mod chart;
 pub(crate) mod client;
+pub(crate) mod error;
+pub(crate) mod export;
 pub(crate) mod ipc;
+pub(crate) mod latest;
+pub(crate) mod message_view;
 pub(crate) mod mqtt;
+pub(crate) mod presets;
+pub(crate) mod protobuf;
+pub(crate) mod stats;
+pub(crate) mod sys;
+pub(crate) mod toast;
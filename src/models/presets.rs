@@ -0,0 +1,120 @@
+use crate::models::mqtt::{ConnectionInputMode, MqttLoginData, TlsVerificationMode, TransportKind};
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct BrokerPreset {
+    pub(crate) name: &'static str,
+    pub(crate) host_hint: &'static str,
+    pub(crate) transport: TransportKind,
+    pub(crate) port: u16,
+    pub(crate) ws_path: &'static str,
+    pub(crate) alpn: &'static str,
+    pub(crate) requires_client_cert: bool,
+    pub(crate) notes: &'static str,
+}
+
+pub(crate) const BROKER_PRESETS: &[BrokerPreset] = &[
+    BrokerPreset {
+        name: "AWS IoT Core",
+        host_hint: "<prefix>-ats.iot.<region>.amazonaws.com",
+        transport: TransportKind::Tls,
+        port: 8883,
+        ws_path: "/mqtt",
+        alpn: "",
+        requires_client_cert: true,
+        notes: "Use your device data endpoint and attach the thing's certificate and private key.",
+    },
+    BrokerPreset {
+        name: "AWS IoT Core (port 443)",
+        host_hint: "<prefix>-ats.iot.<region>.amazonaws.com",
+        transport: TransportKind::Tls,
+        port: 443,
+        ws_path: "/mqtt",
+        alpn: "x-amzn-mqtt-ca",
+        requires_client_cert: true,
+        notes: "For networks that only allow 443. Attach the thing's certificate and private key.",
+    },
+    BrokerPreset {
+        name: "HiveMQ Cloud",
+        host_hint: "<cluster-id>.s1.eu.hivemq.cloud",
+        transport: TransportKind::Tls,
+        port: 8883,
+        ws_path: "/mqtt",
+        alpn: "",
+        requires_client_cert: false,
+        notes: "Use the cluster URL and the credentials created under Access Management.",
+    },
+    BrokerPreset {
+        name: "HiveMQ Cloud (WebSocket)",
+        host_hint: "<cluster-id>.s1.eu.hivemq.cloud",
+        transport: TransportKind::Wss,
+        port: 8884,
+        ws_path: "/mqtt",
+        alpn: "",
+        requires_client_cert: false,
+        notes: "Use the cluster URL and the credentials created under Access Management.",
+    },
+    BrokerPreset {
+        name: "EMQX Cloud",
+        host_hint: "<deployment>.<region>.emqxsl.com",
+        transport: TransportKind::Tls,
+        port: 8883,
+        ws_path: "/mqtt",
+        alpn: "",
+        requires_client_cert: false,
+        notes: "Use the deployment address and a username/password from Authentication.",
+    },
+    BrokerPreset {
+        name: "Azure IoT Hub",
+        host_hint: "<hub-name>.azure-devices.net",
+        transport: TransportKind::Tls,
+        port: 8883,
+        ws_path: "/$iothub/websocket",
+        alpn: "",
+        requires_client_cert: false,
+        notes: "Use the device ID as client ID and '<hub>.azure-devices.net/<device-id>/?api-version=2021-04-12' as username, with a SAS token password or an X.509 client certificate.",
+    },
+];
+
+impl BrokerPreset {
+    pub(crate) fn apply(&self, login: &mut MqttLoginData) {
+        login.connection_mode = ConnectionInputMode::Structured;
+        login.transport = self.transport;
+        login.port = self.port.to_string();
+        login.ws_path = self.ws_path.to_string();
+        login.tls_verification = TlsVerificationMode::SystemRoots;
+        login.tls_ca_cert_path.clear();
+        login.tls_alpn = self.alpn.to_string();
+        if login.name.trim().is_empty() {
+            login.name = self.name.to_string();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BROKER_PRESETS;
+    use crate::models::mqtt::{ConnectionInputMode, MqttLoginData, TlsVerificationMode};
+
+    #[test]
+    fn presets_configure_tls_and_keep_credentials() {
+        for preset in BROKER_PRESETS {
+            let mut login = MqttLoginData {
+                connection_mode: ConnectionInputMode::Url,
+                tls_verification: TlsVerificationMode::InsecureSkipVerify,
+                username: "device".to_string(),
+                password: "secret".to_string(),
+                ..MqttLoginData::default()
+            };
+            preset.apply(&mut login);
+
+            assert_eq!(login.connection_mode, ConnectionInputMode::Structured);
+            assert!(login.transport.uses_tls(), "{}", preset.name);
+            assert_eq!(login.tls_verification, TlsVerificationMode::SystemRoots);
+            assert_eq!(login.port, preset.port.to_string());
+            assert_eq!(login.tls_alpn, preset.alpn);
+            assert_eq!(login.name, preset.name);
+            assert_eq!(login.username, "device");
+            assert_eq!(login.password, "secret");
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use prost_reflect::{DescriptorPool, DynamicMessage};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::topic::topic_matches;
+
+/// Message types loaded from a compiled `FileDescriptorSet`, e.g. the output of
+/// `protoc --include_imports --descriptor_set_out=schema.desc`.
+#[derive(Clone, Debug)]
+pub(crate) struct ProtobufSchema {
+    pub(crate) path: PathBuf,
+    pool: DescriptorPool,
+}
+
+impl ProtobufSchema {
+    pub(crate) fn load(path: &Path) -> Result<Self, String> {
+        let bytes =
+            fs::read(path).map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+        let pool = DescriptorPool::decode(bytes.as_slice()).map_err(|err| {
+            format!(
+                "{} is not a protobuf FileDescriptorSet: {err}",
+                path.display()
+            )
+        })?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            pool,
+        })
+    }
+
+    pub(crate) fn message_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .pool
+            .all_messages()
+            .map(|message| message.full_name().to_string())
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    pub(crate) fn has_message(&self, name: &str) -> bool {
+        self.pool.get_message_by_name(name).is_some()
+    }
+
+    /// Decodes `payload` as `message_type` and renders it as pretty-printed JSON.
+    pub(crate) fn decode(&self, message_type: &str, payload: &[u8]) -> Result<String, String> {
+        let descriptor = self
+            .pool
+            .get_message_by_name(message_type)
+            .ok_or_else(|| format!("Unknown message type {message_type}"))?;
+        let message = DynamicMessage::decode(descriptor, payload)
+            .map_err(|err| format!("Not a valid {message_type}: {err}"))?;
+        serde_json::to_string_pretty(&message).map_err(|err| err.to_string())
+    }
+}
+
+/// Decodes payloads on topics matching `topic_filter` as `message_type`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ProtobufMapping {
+    pub(crate) topic_filter: String,
+    pub(crate) message_type: String,
+}
+
+pub(crate) fn message_type_for<'a>(
+    mappings: &'a [ProtobufMapping],
+    topic: &str,
+) -> Option<&'a str> {
+    mappings
+        .iter()
+        .find(|mapping| topic_matches(&mapping.topic_filter, topic))
+        .map(|mapping| mapping.message_type.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use prost_reflect::prost::Message;
+    use prost_reflect::prost_types::{
+        DescriptorProto, FieldDescriptorProto, FileDescriptorProto, FileDescriptorSet,
+        field_descriptor_proto::{Label, Type},
+    };
+
+    use super::{ProtobufMapping, ProtobufSchema, message_type_for};
+
+    fn reading_schema() -> ProtobufSchema {
+        let field = |name: &str, number, kind: Type| FieldDescriptorProto {
+            name: Some(name.to_string()),
+            number: Some(number),
+            label: Some(Label::Optional as i32),
+            r#type: Some(kind as i32),
+            json_name: Some(name.to_string()),
+            ..Default::default()
+        };
+        let set = FileDescriptorSet {
+            file: vec![FileDescriptorProto {
+                name: Some("reading.proto".to_string()),
+                package: Some("iot".to_string()),
+                syntax: Some("proto3".to_string()),
+                message_type: vec![DescriptorProto {
+                    name: Some("Reading".to_string()),
+                    field: vec![
+                        field("sensor", 1, Type::String),
+                        field("celsius", 2, Type::Double),
+                    ],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+        };
+
+        let path = std::env::temp_dir().join(format!("mqui-test-{}.desc", std::process::id()));
+        std::fs::write(&path, set.encode_to_vec()).unwrap();
+        let schema = ProtobufSchema::load(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        schema
+    }
+
+    #[test]
+    fn decodes_payloads_with_a_descriptor_set() {
+        let schema = reading_schema();
+        assert_eq!(schema.message_names(), ["iot.Reading"]);
+
+        // sensor = "a", celsius = 21.5
+        let mut payload = vec![0x0a, 0x01, b'a', 0x11];
+        payload.extend_from_slice(&21.5f64.to_le_bytes());
+        let json = schema.decode("iot.Reading", &payload).unwrap();
+        assert!(json.contains("\"sensor\": \"a\""));
+        assert!(json.contains("\"celsius\": 21.5"));
+
+        assert!(schema.decode("iot.Missing", &payload).is_err());
+        assert!(schema.decode("iot.Reading", &[0x0a, 0x05]).is_err());
+    }
+
+    #[test]
+    fn first_matching_mapping_wins() {
+        let mappings = [
+            ProtobufMapping {
+                topic_filter: "sensors/+/reading".to_string(),
+                message_type: "iot.Reading".to_string(),
+            },
+            ProtobufMapping {
+                topic_filter: "sensors/#".to_string(),
+                message_type: "iot.Other".to_string(),
+            },
+        ];
+        assert_eq!(
+            message_type_for(&mappings, "sensors/a/reading"),
+            Some("iot.Reading")
+        );
+        assert_eq!(message_type_for(&mappings, "sensors/a"), Some("iot.Other"));
+        assert_eq!(message_type_for(&mappings, "other"), None);
+    }
+}
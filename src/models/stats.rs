@@ -0,0 +1,208 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::time::{Duration, SystemTime};
+
+const CHANGE_HIGHLIGHT: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum TopicStatsSort {
+    Topic,
+    #[default]
+    Count,
+    Bytes,
+    LastSeen,
+}
+
+impl TopicStatsSort {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::Topic => "Topic",
+            Self::Count => "Messages",
+            Self::Bytes => "Bytes",
+            Self::LastSeen => "Last seen",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct TopicStat {
+    pub(crate) count: u64,
+    pub(crate) bytes: u64,
+    pub(crate) last_seen: SystemTime,
+    pub(crate) changed_at: Option<SystemTime>,
+    payload_hash: u64,
+}
+
+impl TopicStat {
+    pub(crate) fn change_highlight(&self) -> f32 {
+        let Some(elapsed) = self.changed_at.and_then(|at| at.elapsed().ok()) else {
+            return 0.0;
+        };
+        1.0 - (elapsed.as_secs_f32() / CHANGE_HIGHLIGHT.as_secs_f32()).min(1.0)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TopicStats {
+    topics: HashMap<String, TopicStat>,
+}
+
+impl TopicStats {
+    pub(crate) fn record(&mut self, topic: &str, payload: &[u8], at: SystemTime) {
+        self.record_many(topic, payload, 1, payload.len() as u64, at);
+    }
+
+    /// Adds `count` messages totalling `bytes` at once, `payload` being the newest of them.
+    pub(crate) fn record_many(
+        &mut self,
+        topic: &str,
+        payload: &[u8],
+        count: u64,
+        bytes: u64,
+        at: SystemTime,
+    ) {
+        let mut hasher = DefaultHasher::new();
+        payload.hash(&mut hasher);
+        let payload_hash = hasher.finish();
+
+        let stat = self.topics.entry(topic.to_string()).or_insert(TopicStat {
+            count: 0,
+            bytes: 0,
+            last_seen: at,
+            changed_at: None,
+            payload_hash,
+        });
+        if stat.payload_hash != payload_hash {
+            stat.payload_hash = payload_hash;
+            stat.changed_at = Some(at);
+        }
+        stat.count += count;
+        stat.bytes += bytes;
+        stat.last_seen = at;
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.topics.len()
+    }
+
+    pub(crate) fn get(&self, topic: &str) -> Option<&TopicStat> {
+        self.topics.get(topic)
+    }
+
+    pub(crate) fn topics(&self) -> impl Iterator<Item = &str> {
+        self.topics.keys().map(String::as_str)
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.topics.clear();
+    }
+
+    pub(crate) fn sorted(&self, sort: TopicStatsSort) -> Vec<(&str, &TopicStat)> {
+        let mut rows: Vec<_> = self
+            .topics
+            .iter()
+            .map(|(topic, stat)| (topic.as_str(), stat))
+            .collect();
+        rows.sort_by(|(a_topic, a), (b_topic, b)| match sort {
+            TopicStatsSort::Topic => a_topic.cmp(b_topic),
+            TopicStatsSort::Count => Reverse(a.count)
+                .cmp(&Reverse(b.count))
+                .then(a_topic.cmp(b_topic)),
+            TopicStatsSort::Bytes => Reverse(a.bytes)
+                .cmp(&Reverse(b.bytes))
+                .then(a_topic.cmp(b_topic)),
+            TopicStatsSort::LastSeen => Reverse(a.last_seen)
+                .cmp(&Reverse(b.last_seen))
+                .then(a_topic.cmp(b_topic)),
+        });
+        rows
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct RttSummary {
+    pub(crate) received: u32,
+    pub(crate) lost: u32,
+    pub(crate) min: Duration,
+    pub(crate) max: Duration,
+    total: Duration,
+}
+
+impl RttSummary {
+    pub(crate) fn record(&mut self, rtt: Duration) {
+        self.min = if self.received == 0 {
+            rtt
+        } else {
+            self.min.min(rtt)
+        };
+        self.max = self.max.max(rtt);
+        self.total += rtt;
+        self.received += 1;
+    }
+
+    pub(crate) fn average(&self) -> Option<Duration> {
+        (self.received > 0).then(|| self.total / self.received)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::{RttSummary, TopicStats, TopicStatsSort};
+
+    #[test]
+    fn tallies_are_sorted_by_the_selected_column() {
+        let start = UNIX_EPOCH + Duration::from_secs(100);
+        let mut stats = TopicStats::default();
+        stats.record("a", &[0; 10], start);
+        stats.record("b", &[0], start + Duration::from_secs(1));
+        stats.record("b", &[0], start + Duration::from_secs(2));
+        stats.record("c", &[0; 50], start);
+
+        let order = |sort| -> Vec<_> {
+            stats
+                .sorted(sort)
+                .into_iter()
+                .map(|(topic, _)| topic.to_string())
+                .collect()
+        };
+        assert_eq!(order(TopicStatsSort::Topic), ["a", "b", "c"]);
+        assert_eq!(order(TopicStatsSort::Count), ["b", "a", "c"]);
+        assert_eq!(order(TopicStatsSort::Bytes), ["c", "a", "b"]);
+        assert_eq!(order(TopicStatsSort::LastSeen), ["b", "a", "c"]);
+
+        let (_, b) = stats.sorted(TopicStatsSort::Topic)[1];
+        assert_eq!((b.count, b.bytes), (2, 2));
+    }
+
+    #[test]
+    fn change_time_tracks_payload_changes_only() {
+        let start = UNIX_EPOCH + Duration::from_secs(100);
+        let mut stats = TopicStats::default();
+        let changed_at = |stats: &TopicStats| stats.sorted(TopicStatsSort::Topic)[0].1.changed_at;
+
+        stats.record("t", b"1", start);
+        assert_eq!(changed_at(&stats), None);
+        stats.record("t", b"1", start + Duration::from_secs(1));
+        assert_eq!(changed_at(&stats), None);
+        stats.record("t", b"2", start + Duration::from_secs(2));
+        assert_eq!(changed_at(&stats), Some(start + Duration::from_secs(2)));
+        stats.record("t", b"2", start + Duration::from_secs(3));
+        assert_eq!(changed_at(&stats), Some(start + Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn rtt_summary_tracks_min_avg_max() {
+        let mut summary = RttSummary::default();
+        assert_eq!(summary.average(), None);
+
+        for millis in [30, 10, 20] {
+            summary.record(Duration::from_millis(millis));
+        }
+        assert_eq!(summary.min, Duration::from_millis(10));
+        assert_eq!(summary.max, Duration::from_millis(30));
+        assert_eq!(summary.average(), Some(Duration::from_millis(20)));
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::{HashMap, HashSet};
+use std::time::SystemTime;
+
+use crate::utils::topic::topic_matches;
+
+pub(crate) const SYS_SUBSCRIPTION: &str = "$SYS/#";
+
+const KNOWN_SYS_TOPICS: &[(&str, &str)] = &[
+    ("$SYS/broker/version", "Version"),
+    ("$SYS/brokers/+/version", "Version"),
+    ("$SYS/broker/uptime", "Uptime"),
+    ("$SYS/brokers/+/uptime", "Uptime"),
+    ("$SYS/broker/clients/connected", "Connected clients"),
+    ("$SYS/broker/clients/active", "Connected clients"),
+    ("$SYS/broker/clients/disconnected", "Disconnected clients"),
+    ("$SYS/broker/clients/total", "Total clients"),
+    ("$SYS/broker/clients/maximum", "Peak clients"),
+    ("$SYS/broker/subscriptions/count", "Subscriptions"),
+    ("$SYS/broker/retained messages/count", "Retained messages"),
+    ("$SYS/broker/messages/received", "Messages received"),
+    ("$SYS/broker/messages/sent", "Messages sent"),
+    (
+        "$SYS/broker/publish/messages/received",
+        "Publishes received",
+    ),
+    ("$SYS/broker/publish/messages/sent", "Publishes sent"),
+    ("$SYS/broker/publish/messages/dropped", "Publishes dropped"),
+    ("$SYS/broker/bytes/received", "Bytes received"),
+    ("$SYS/broker/bytes/sent", "Bytes sent"),
+    (
+        "$SYS/broker/load/messages/received/1min",
+        "Messages received / min",
+    ),
+    ("$SYS/broker/load/messages/sent/1min", "Messages sent / min"),
+    ("$SYS/broker/heap/current", "Heap in use"),
+];
+
+#[derive(Clone, Debug)]
+pub(crate) struct SysValue {
+    pub(crate) value: String,
+    pub(crate) topic: String,
+    pub(crate) updated: SystemTime,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SysStats {
+    known: HashMap<&'static str, SysValue>,
+    pub(crate) other_topics: usize,
+    seen_other: HashSet<String>,
+}
+
+impl SysStats {
+    pub(crate) fn record(&mut self, topic: &str, payload: &[u8], at: SystemTime) {
+        if !topic.starts_with("$SYS/") {
+            return;
+        }
+
+        match sys_label(topic) {
+            Some(label) => {
+                self.known.insert(
+                    label,
+                    SysValue {
+                        value: String::from_utf8_lossy(payload).trim().to_string(),
+                        topic: topic.to_string(),
+                        updated: at,
+                    },
+                );
+            }
+            None => {
+                if self.seen_other.insert(topic.to_string()) {
+                    self.other_topics += 1;
+                }
+            }
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.known.is_empty() && self.other_topics == 0
+    }
+
+    pub(crate) fn rows(&self) -> Vec<(&'static str, Option<&SysValue>)> {
+        let mut labels: Vec<&'static str> = Vec::new();
+        for (_, label) in KNOWN_SYS_TOPICS {
+            if !labels.contains(label) {
+                labels.push(label);
+            }
+        }
+        labels
+            .into_iter()
+            .map(|label| (label, self.known.get(label)))
+            .collect()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+pub(crate) fn sys_label(topic: &str) -> Option<&'static str> {
+    KNOWN_SYS_TOPICS
+        .iter()
+        .find(|(pattern, _)| topic_matches(pattern, topic))
+        .map(|(_, label)| *label)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use super::{SysStats, sys_label};
+
+    #[test]
+    fn known_topics_map_to_labels() {
+        assert_eq!(sys_label("$SYS/broker/uptime"), Some("Uptime"));
+        assert_eq!(sys_label("$SYS/brokers/emqx@node1/uptime"), Some("Uptime"));
+        assert_eq!(
+            sys_label("$SYS/broker/clients/active"),
+            Some("Connected clients")
+        );
+        assert_eq!(sys_label("$SYS/broker/unknown"), None);
+    }
+
+    #[test]
+    fn stats_keep_latest_values_and_count_unknown_topics() {
+        let mut stats = SysStats::default();
+        stats.record("$SYS/broker/clients/connected", b"3", UNIX_EPOCH);
+        stats.record("$SYS/broker/clients/connected", b" 4\n", UNIX_EPOCH);
+        stats.record("$SYS/broker/custom", b"x", UNIX_EPOCH);
+        stats.record("$SYS/broker/custom", b"y", UNIX_EPOCH);
+        stats.record("sensors/temp", b"21", UNIX_EPOCH);
+
+        let rows = stats.rows();
+        let connected = rows
+            .iter()
+            .find(|(label, _)| *label == "Connected clients")
+            .and_then(|(_, value)| *value)
+            .unwrap();
+        assert_eq!(connected.value, "4");
+        assert_eq!(stats.other_topics, 1);
+        assert_eq!(
+            rows.iter().filter(|(label, _)| *label == "Uptime").count(),
+            1
+        );
+    }
+}
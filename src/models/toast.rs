@@ -0,0 +1,80 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// How long a toast stays up unless dismissed.
+pub(crate) const TOAST_DURATION: Duration = Duration::from_secs(6);
+const MAX_TOASTS: usize = 5;
+
+#[derive(Clone, Debug)]
+pub(crate) struct Toast {
+    pub(crate) id: u64,
+    pub(crate) tab_id: u64,
+    pub(crate) title: String,
+    pub(crate) text: String,
+    shown_at: Instant,
+}
+
+impl Toast {
+    pub(crate) fn remaining(&self, now: Instant) -> Option<Duration> {
+        TOAST_DURATION
+            .checked_sub(now.saturating_duration_since(self.shown_at))
+            .filter(|remaining| !remaining.is_zero())
+    }
+}
+
+/// Transient notifications for errors worth noticing from any tab, newest last.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ToastQueue {
+    toasts: VecDeque<Toast>,
+    next_id: u64,
+}
+
+impl ToastQueue {
+    pub(crate) fn push(&mut self, tab_id: u64, title: String, text: String, now: Instant) {
+        self.toasts.push_back(Toast {
+            id: self.next_id,
+            tab_id,
+            title,
+            text,
+            shown_at: now,
+        });
+        self.next_id += 1;
+        while self.toasts.len() > MAX_TOASTS {
+            let _ = self.toasts.pop_front();
+        }
+    }
+
+    pub(crate) fn dismiss(&mut self, id: u64) {
+        self.toasts.retain(|toast| toast.id != id);
+    }
+
+    /// Drops expired toasts and returns the ones still showing.
+    pub(crate) fn visible(&mut self, now: Instant) -> &VecDeque<Toast> {
+        self.toasts.retain(|toast| toast.remaining(now).is_some());
+        &self.toasts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::{MAX_TOASTS, TOAST_DURATION, ToastQueue};
+
+    #[test]
+    fn toasts_expire_and_can_be_dismissed() {
+        let start = Instant::now();
+        let mut toasts = ToastQueue::default();
+        for index in 0..MAX_TOASTS + 1 {
+            toasts.push(1, "Tab".to_string(), index.to_string(), start);
+        }
+        let visible = toasts.visible(start);
+        assert_eq!(visible.len(), MAX_TOASTS);
+        assert_eq!(visible[0].text, "1");
+
+        let first = visible[0].id;
+        toasts.dismiss(first);
+        assert_eq!(toasts.visible(start).len(), MAX_TOASTS - 1);
+        assert!(toasts.visible(start + TOAST_DURATION).is_empty());
+    }
+}
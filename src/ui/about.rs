@@ -0,0 +1,55 @@
+use eframe::egui;
+
+use crate::app::App;
+
+// Read from Cargo.lock by build.rs.
+const MQTT_ENDPOINT_VERSION: &str = env!("MQTT_ENDPOINT_VERSION");
+const SUPPORTED_MQTT_VERSIONS: &str = "MQTT 5.0";
+
+pub(crate) fn render_about_window(app: &mut App, ctx: &egui::Context) {
+    if !app.show_about {
+        return;
+    }
+
+    let mut open = app.show_about;
+    egui::Window::new("About MQUI")
+        .collapsible(false)
+        .resizable(false)
+        .open(&mut open)
+        .show(ctx, |ui| {
+            ui.heading(format!("MQUI {}", env!("CARGO_PKG_VERSION")));
+            egui::Grid::new("about_grid").show(ui, |ui| {
+                ui.label("Protocol");
+                ui.label(SUPPORTED_MQTT_VERSIONS);
+                ui.end_row();
+                ui.label("mqtt-endpoint-tokio");
+                ui.label(MQTT_ENDPOINT_VERSION);
+                ui.end_row();
+                ui.label("Target");
+                ui.label(format!(
+                    "{} {}",
+                    std::env::consts::OS,
+                    std::env::consts::ARCH
+                ));
+                ui.end_row();
+                ui.label("Source");
+                ui.hyperlink(env!("CARGO_PKG_REPOSITORY"));
+                ui.end_row();
+            });
+            ui.add_space(4.0);
+            if ui
+                .button("Copy version info")
+                .on_hover_text("Paste this into bug reports")
+                .clicked()
+            {
+                ui.ctx().copy_text(format!(
+                    "MQUI {} ({SUPPORTED_MQTT_VERSIONS}, mqtt-endpoint-tokio {MQTT_ENDPOINT_VERSION}, {} {})",
+                    env!("CARGO_PKG_VERSION"),
+                    std::env::consts::OS,
+                    std::env::consts::ARCH
+                ));
+            }
+        });
+
+    app.show_about = open;
+}
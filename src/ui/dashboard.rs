@@ -0,0 +1,118 @@
+use eframe::egui;
+
+use crate::models::latest::LatestByTopic;
+use crate::models::stats::TopicStats;
+use crate::ui::{topic_color_for, topic_label};
+use crate::utils::formatting::{PayloadEncoding, format_payload};
+
+const CELL_WIDTH: f32 = 180.0;
+const MAX_CELL_CHARS: usize = 200;
+
+pub(crate) struct Dashboard<'a> {
+    pub(crate) id: u64,
+    pub(crate) topics: &'a mut Vec<String>,
+    pub(crate) input: &'a mut String,
+    pub(crate) latest: &'a LatestByTopic,
+    pub(crate) topic_stats: &'a TopicStats,
+    pub(crate) encoding: PayloadEncoding,
+}
+
+/// One cell per chosen topic showing its latest payload, updated in place.
+pub(crate) fn render_dashboard(ui: &mut egui::Ui, dashboard: Dashboard<'_>) {
+    let Dashboard {
+        id,
+        topics,
+        input,
+        latest,
+        topic_stats,
+        encoding,
+    } = dashboard;
+
+    ui.horizontal(|ui| {
+        ui.add(egui::TextEdit::singleline(input).hint_text("sensors/room1/temperature"));
+        if ui.button("Add").clicked() {
+            let topic = input.trim().to_string();
+            if !topic.is_empty() && !topics.contains(&topic) {
+                topics.push(topic);
+            }
+            input.clear();
+        }
+        ui.menu_button("Add seen topic", |ui| {
+            let mut seen: Vec<&str> = topic_stats
+                .topics()
+                .filter(|topic| !topics.iter().any(|chosen| chosen == topic))
+                .collect();
+            seen.sort_unstable();
+            if seen.is_empty() {
+                ui.label("No other topics received yet");
+            }
+            egui::ScrollArea::vertical()
+                .id_salt(("dashboard_seen_topics", id))
+                .max_height(240.0)
+                .show(ui, |ui| {
+                    for topic in seen {
+                        if ui.button(topic).clicked() {
+                            topics.push(topic.to_string());
+                            ui.close();
+                        }
+                    }
+                });
+        });
+    });
+
+    if topics.is_empty() {
+        ui.label("Add topics to watch their latest value here.");
+        return;
+    }
+
+    let mut remove = None;
+    ui.horizontal_wrapped(|ui| {
+        for (index, topic) in topics.iter().enumerate() {
+            let latest = latest.get(topic);
+            let highlight = topic_stats
+                .get(topic)
+                .map(|stat| stat.change_highlight())
+                .unwrap_or_default();
+            let mut frame = egui::Frame::group(ui.style());
+            if highlight > 0.0 {
+                frame = frame.fill(ui.visuals().selection.bg_fill.gamma_multiply(highlight));
+            }
+            frame.show(ui, |ui| {
+                ui.set_width(CELL_WIDTH);
+                ui.vertical(|ui| {
+                    ui.horizontal(|ui| {
+                        let color = topic_color_for(topic, ui.visuals());
+                        topic_label(ui, topic, color);
+                        if ui.small_button("x").on_hover_text("Remove").clicked() {
+                            remove = Some(index);
+                        }
+                    });
+                    match latest {
+                        Some(msg) => {
+                            let mut text = format_payload(&msg.payload, encoding);
+                            if let Some((cut, _)) = text.char_indices().nth(MAX_CELL_CHARS) {
+                                text.truncate(cut);
+                                text.push('…');
+                            }
+                            ui.label(egui::RichText::new(text).monospace().strong());
+                            let ago = msg
+                                .timestamp
+                                .elapsed()
+                                .map(|elapsed| elapsed.as_secs())
+                                .unwrap_or_default();
+                            let retained = if msg.retain { ", retained" } else { "" };
+                            ui.weak(format!("{ago}s ago{retained}"));
+                        }
+                        None => {
+                            ui.weak("No value yet");
+                        }
+                    }
+                });
+            });
+        }
+    });
+
+    if let Some(index) = remove {
+        topics.remove(index);
+    }
+}
@@ -0,0 +1,303 @@
+use eframe::egui;
+
+use crate::models::export::ExportedMessage;
+use crate::models::ipc::ClientCommand;
+use crate::models::message_view::MessageRow;
+use crate::models::mqtt::ReceivedMessage;
+use crate::ui::{topic_color_for, topic_label};
+use crate::utils::formatting::{
+    PayloadEncoding, format_payload, format_timestamp, interpret_number, parse_epoch_timestamp,
+};
+use crate::utils::topic::subscription_patterns;
+
+pub(crate) enum MessageAction {
+    Ack(u16),
+    Chart(String),
+    Dashboard(String),
+    Mute(String),
+    Subscribe { topic: String, qos: u8 },
+    Publish(ClientCommand),
+    SetPublishTopic(String),
+}
+
+pub(crate) struct PayloadView<'a> {
+    pub(crate) encoding: PayloadEncoding,
+    pub(crate) interpret_numbers: bool,
+    /// Hides the actions that publish, for read-only monitor tabs.
+    pub(crate) read_only: bool,
+    pub(crate) broker_timestamp_property: &'a str,
+}
+
+/// Width of each JSON path column, shared by the header row and the message cards.
+pub(crate) const JSON_COLUMN_WIDTH: f32 = 120.0;
+
+pub(crate) fn message_card(
+    ui: &mut egui::Ui,
+    row: &MessageRow,
+    view: &PayloadView<'_>,
+) -> Option<MessageAction> {
+    let msg = &row.message;
+    let repeats = row.repeats;
+    let (ts, ts_hint) = header_timestamp(msg, view.broker_timestamp_property);
+
+    let mut action = None;
+    let card = ui.group(|ui| {
+        ui.horizontal_wrapped(|ui| {
+            let ts_label = ui.label(format!("[{ts}] "));
+            if let Some(hint) = ts_hint {
+                ts_label.on_hover_text(hint);
+            }
+            let color = topic_color_for(&msg.topic, ui.visuals());
+            topic_label(ui, &msg.topic, color);
+            if repeats > 1 {
+                ui.label(egui::RichText::new(format!("×{repeats}")).strong())
+                    .on_hover_text("Identical consecutive messages collapsed into this row");
+            }
+            if msg.retained_on_subscribe {
+                ui.label(
+                    egui::RichText::new("retained on subscribe")
+                        .color(ui.visuals().warn_fg_color)
+                        .italics(),
+                )
+                .on_hover_text(
+                    "Delivered by the broker because a matching subscription was just made",
+                );
+            }
+            if msg.ack_pending
+                && let Some(packet_id) = msg.packet_id
+                && ui
+                    .small_button(if msg.qos == 2 {
+                        "Ack (PUBREC)"
+                    } else {
+                        "Ack (PUBACK)"
+                    })
+                    .on_hover_text(format!("Acknowledge packet id {packet_id}"))
+                    .clicked()
+            {
+                action = Some(MessageAction::Ack(packet_id));
+            }
+            if let Some(request) = msg.reply_to {
+                ui.label(
+                    egui::RichText::new(format!("reply to #{request}"))
+                        .color(ui.visuals().hyperlink_color)
+                        .strong(),
+                );
+            }
+        });
+        if !row.columns.is_empty() {
+            ui.horizontal(|ui| {
+                for value in &row.columns {
+                    ui.add_sized(
+                        [JSON_COLUMN_WIDTH, ui.spacing().interact_size.y],
+                        egui::Label::new(egui::RichText::new(value).monospace()).truncate(),
+                    );
+                }
+            });
+        }
+        ui.label(message_details(msg));
+        if let Some(broker_time) = broker_timestamp(msg, view.broker_timestamp_property) {
+            ui.label(broker_time);
+        }
+        if let Some(err) = &row.protobuf_error {
+            ui.colored_label(ui.visuals().warn_fg_color, err);
+        }
+        ui.label(&row.payload_text);
+        if view.interpret_numbers
+            && let Some(reading) = interpret_number(&msg.payload)
+        {
+            ui.weak(format!("= {reading}"));
+        }
+    });
+
+    card.response
+        .interact(egui::Sense::click())
+        .context_menu(|ui| {
+            if ui.button("Copy payload").clicked() {
+                ui.ctx()
+                    .copy_text(format_payload(&msg.payload, view.encoding));
+                ui.close();
+            }
+            if ui.button("Copy topic").clicked() {
+                ui.ctx().copy_text(msg.topic.clone());
+                ui.close();
+            }
+            if ui.button("Copy as JSON").clicked() {
+                if let Ok(json) = ExportedMessage::from_message(msg).to_json() {
+                    ui.ctx().copy_text(json);
+                }
+                ui.close();
+            }
+            ui.separator();
+            if !view.read_only && ui.button("Republish").clicked() {
+                action = Some(MessageAction::Publish(ClientCommand::Publish {
+                    topic: msg.topic.clone(),
+                    payload: msg.payload.clone(),
+                    qos: msg.qos,
+                    retain: msg.retain,
+                    properties: msg.properties.clone(),
+                }));
+                ui.close();
+            }
+            if !view.read_only {
+                let response_topic = msg.properties.response_topic.as_ref();
+                match response_topic.filter(|topic| **topic != msg.topic) {
+                    Some(response_topic) => {
+                        ui.menu_button("Use as publish topic", |ui| {
+                            for topic in [&msg.topic, response_topic] {
+                                if ui.button(topic).clicked() {
+                                    action = Some(MessageAction::SetPublishTopic(topic.clone()));
+                                    ui.close();
+                                }
+                            }
+                        });
+                    }
+                    None => {
+                        if ui.button("Use as publish topic").clicked() {
+                            action = Some(MessageAction::SetPublishTopic(msg.topic.clone()));
+                            ui.close();
+                        }
+                    }
+                }
+            }
+            ui.menu_button("Subscribe to", |ui| {
+                for pattern in subscription_patterns(&msg.topic) {
+                    if ui.button(&pattern).clicked() {
+                        action = Some(MessageAction::Subscribe {
+                            topic: pattern,
+                            qos: msg.qos,
+                        });
+                        ui.close();
+                    }
+                }
+            });
+            if ui.button("Chart topic").clicked() {
+                action = Some(MessageAction::Chart(msg.topic.clone()));
+                ui.close();
+            }
+            if ui.button("Add to dashboard").clicked() {
+                action = Some(MessageAction::Dashboard(msg.topic.clone()));
+                ui.close();
+            }
+            if ui.button("Mute topic").clicked() {
+                action = Some(MessageAction::Mute(msg.topic.clone()));
+                ui.close();
+            }
+            if !view.read_only {
+                ui.separator();
+            }
+            if !view.read_only && ui.button("Clear retained").clicked() {
+                action = Some(MessageAction::Publish(ClientCommand::Publish {
+                    topic: msg.topic.clone(),
+                    payload: Vec::new(),
+                    qos: msg.qos,
+                    retain: true,
+                    properties: Default::default(),
+                }));
+                ui.close();
+            }
+        });
+
+    action
+}
+
+/// Retained messages can be hours old, so their header says whether the time shown is when the
+/// value was published (from the broker timestamp property) or merely when it arrived here.
+fn header_timestamp(msg: &ReceivedMessage, property: &str) -> (String, Option<&'static str>) {
+    if !msg.retain {
+        return (format_timestamp(msg.timestamp), None);
+    }
+
+    let published = Some(property.trim())
+        .filter(|property| !property.is_empty())
+        .and_then(|property| msg.properties.user_property(property))
+        .and_then(parse_epoch_timestamp);
+    match published {
+        Some(sent) => (
+            format!("sent {}", format_timestamp(sent)),
+            Some("Publish time of the retained value, from the broker timestamp property"),
+        ),
+        None => (
+            format!("received {}", format_timestamp(msg.timestamp)),
+            Some("The broker does not report when a retained value was originally published"),
+        ),
+    }
+}
+
+fn broker_timestamp(msg: &ReceivedMessage, property: &str) -> Option<String> {
+    let property = property.trim();
+    if property.is_empty() {
+        return None;
+    }
+
+    let raw = msg.properties.user_property(property)?;
+    let Some(sent) = parse_epoch_timestamp(raw) else {
+        return Some(format!("broker time {raw} (not a Unix timestamp)"));
+    };
+    Some(match msg.timestamp.duration_since(sent) {
+        Ok(latency) => format!(
+            "broker time {} | latency {}ms",
+            format_timestamp(sent),
+            latency.as_millis()
+        ),
+        Err(err) => format!(
+            "broker time {} | {}ms ahead of local clock",
+            format_timestamp(sent),
+            err.duration().as_millis()
+        ),
+    })
+}
+
+fn message_details(msg: &ReceivedMessage) -> String {
+    let mut details = format!("QoS {} | retain {}", msg.qos, msg.retain);
+    if let Some(packet_id) = msg.packet_id {
+        details.push_str(&format!(" | packet id {packet_id}"));
+    }
+    if let Some(content_type) = &msg.properties.content_type {
+        details.push_str(&format!(" | {content_type}"));
+    }
+    if msg.properties.payload_is_utf8 {
+        details.push_str(" | UTF-8");
+    }
+    if let Some(response_topic) = &msg.properties.response_topic {
+        details.push_str(&format!(" | response topic {response_topic}"));
+    }
+    if let Some(correlation_data) = &msg.properties.correlation_data {
+        details.push_str(&format!(
+            " | correlation {}",
+            format_payload(correlation_data, PayloadEncoding::Utf8)
+        ));
+    }
+    if let Some(secs) = msg.properties.message_expiry_secs {
+        let elapsed = msg
+            .timestamp
+            .elapsed()
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        let remaining = u64::from(secs).saturating_sub(elapsed);
+        if remaining == 0 {
+            details.push_str(" | expired");
+        } else {
+            details.push_str(&format!(" | expires in {remaining}s"));
+        }
+    }
+    details
+}
+
+#[cfg(test)]
+mod tests {
+    use super::header_timestamp;
+    use crate::models::mqtt::ReceivedMessage;
+
+    #[test]
+    fn retained_messages_label_their_timestamp() {
+        let mut msg = ReceivedMessage::for_test("a", "1");
+        assert_eq!(header_timestamp(&msg, "ts").1, None);
+
+        msg.retain = true;
+        assert!(header_timestamp(&msg, "ts").0.starts_with("received "));
+
+        msg.properties.user_properties = vec![("ts".to_string(), "1700000000".to_string())];
+        assert!(header_timestamp(&msg, "ts").0.starts_with("sent "));
+        assert!(header_timestamp(&msg, "").0.starts_with("received "));
+    }
+}
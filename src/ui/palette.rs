@@ -0,0 +1,242 @@
+use std::time::Instant;
+
+use eframe::egui;
+
+use crate::app::App;
+use crate::app::state::TabState;
+use crate::models::ipc::ClientCommand;
+use crate::models::message_view::ClearedMessages;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PaletteAction {
+    NewTab,
+    CloseTab,
+    CloseAllTabs,
+    DuplicateTab,
+    RenameTab,
+    EditConnection,
+    Reconnect,
+    Disconnect,
+    DisconnectAll,
+    SubscribeAll,
+    ClearMessages,
+    RecordMessages,
+    Settings,
+    About,
+}
+
+impl PaletteAction {
+    pub(crate) const ALL: [Self; 14] = [
+        Self::NewTab,
+        Self::CloseTab,
+        Self::CloseAllTabs,
+        Self::DuplicateTab,
+        Self::RenameTab,
+        Self::EditConnection,
+        Self::Reconnect,
+        Self::Disconnect,
+        Self::DisconnectAll,
+        Self::SubscribeAll,
+        Self::ClearMessages,
+        Self::RecordMessages,
+        Self::Settings,
+        Self::About,
+    ];
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::NewTab => "New tab",
+            Self::CloseTab => "Close tab",
+            Self::CloseAllTabs => "Close all tabs",
+            Self::DuplicateTab => "Duplicate tab",
+            Self::RenameTab => "Rename tab",
+            Self::EditConnection => "Edit connection",
+            Self::Reconnect => "Reconnect",
+            Self::Disconnect => "Disconnect",
+            Self::DisconnectAll => "Disconnect all",
+            Self::SubscribeAll => "Subscribe to #",
+            Self::ClearMessages => "Clear messages",
+            Self::RecordMessages => "Export messages (record to file)",
+            Self::Settings => "Open settings",
+            Self::About => "About MQUI",
+        }
+    }
+
+    fn needs_tab(self) -> bool {
+        !matches!(
+            self,
+            Self::NewTab | Self::CloseAllTabs | Self::DisconnectAll | Self::Settings | Self::About
+        )
+    }
+}
+
+/// Scores `label` against `query` as a case-insensitive subsequence, rewarding consecutive
+/// and word-initial matches. Returns `None` when the query does not match.
+pub(crate) fn fuzzy_score(query: &str, label: &str) -> Option<i32> {
+    let mut score = 0;
+    let mut previous: Option<usize> = None;
+    let label: Vec<char> = label.chars().flat_map(char::to_lowercase).collect();
+    let mut position = 0;
+
+    for wanted in query.chars().flat_map(char::to_lowercase) {
+        if wanted.is_whitespace() {
+            continue;
+        }
+        let offset = label[position..].iter().position(|&ch| ch == wanted)?;
+        let index = position + offset;
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == index) {
+            score += 3;
+        }
+        if index == 0 || !label[index - 1].is_alphanumeric() {
+            score += 2;
+        }
+        previous = Some(index);
+        position = index + 1;
+    }
+
+    Some(score)
+}
+
+pub(crate) fn matching_actions(query: &str) -> Vec<PaletteAction> {
+    let mut scored: Vec<_> = PaletteAction::ALL
+        .into_iter()
+        .filter_map(|action| fuzzy_score(query, action.label()).map(|score| (score, action)))
+        .collect();
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored.into_iter().map(|(_, action)| action).collect()
+}
+
+pub(crate) fn render_command_palette(app: &mut App, ctx: &egui::Context) {
+    if !app.show_palette {
+        return;
+    }
+
+    let has_tab = app.active_tab.is_some();
+    let actions: Vec<_> = matching_actions(&app.palette_query)
+        .into_iter()
+        .filter(|action| has_tab || !action.needs_tab())
+        .collect();
+    app.palette_selected = app.palette_selected.min(actions.len().saturating_sub(1));
+
+    let mut chosen = None;
+    let modal = egui::Modal::new(egui::Id::new("command_palette")).show(ctx, |ui| {
+        ui.set_width(360.0);
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut app.palette_query)
+                .hint_text("Type a command")
+                .desired_width(f32::INFINITY),
+        );
+        response.request_focus();
+        if response.changed() {
+            app.palette_selected = 0;
+        }
+
+        ui.input(|i| {
+            if i.key_pressed(egui::Key::ArrowDown) && app.palette_selected + 1 < actions.len() {
+                app.palette_selected += 1;
+            }
+            if i.key_pressed(egui::Key::ArrowUp) {
+                app.palette_selected = app.palette_selected.saturating_sub(1);
+            }
+            if i.key_pressed(egui::Key::Enter) {
+                chosen = actions.get(app.palette_selected).copied();
+            }
+        });
+
+        ui.separator();
+        if actions.is_empty() {
+            ui.weak("No matching commands");
+        }
+        for (index, action) in actions.iter().enumerate() {
+            if ui
+                .selectable_label(index == app.palette_selected, action.label())
+                .clicked()
+            {
+                chosen = Some(*action);
+            }
+        }
+    });
+
+    if chosen.is_some() || modal.should_close() {
+        app.show_palette = false;
+        app.palette_query.clear();
+        app.palette_selected = 0;
+    }
+    if let Some(action) = chosen {
+        run_action(app, action);
+    }
+}
+
+fn run_action(app: &mut App, action: PaletteAction) {
+    let active = app.active_tab;
+    match (action, active) {
+        (PaletteAction::NewTab, _) => app.show_mqtt_popup = true,
+        (PaletteAction::CloseAllTabs, _) => app.request_close_all_tabs(),
+        (PaletteAction::DisconnectAll, _) => {
+            let ids: Vec<u64> = app.clients.keys().copied().collect();
+            for id in ids {
+                app.disconnect_client(id);
+            }
+        }
+        (PaletteAction::Settings, _) => app.show_settings = true,
+        (PaletteAction::About, _) => app.show_about = true,
+        (_, None) => {}
+        (PaletteAction::CloseTab, Some(id)) => app.close_tab(id),
+        (PaletteAction::DuplicateTab, Some(id)) => app.duplicate_tab(id),
+        (PaletteAction::RenameTab, Some(id)) => {
+            if let Some(tab) = app.tabs.iter().find(|tab| tab.id == id) {
+                app.rename_buffer = tab.title.clone();
+                app.renaming_tab = Some(id);
+            }
+        }
+        (PaletteAction::EditConnection, Some(id)) => app.edit_tab_connection(id),
+        (PaletteAction::Reconnect, Some(id)) => app.reconnect_client(id),
+        (PaletteAction::Disconnect, Some(id)) => app.disconnect_client(id),
+        (PaletteAction::SubscribeAll, Some(id)) => app.send_client_command(
+            id,
+            ClientCommand::Subscribe {
+                topic: "#".to_string(),
+                qos: 0,
+            },
+        ),
+        (PaletteAction::ClearMessages, Some(id)) => {
+            if let Some(tab) = app.tabs.iter_mut().find(|tab| tab.id == id) {
+                let TabState::Client {
+                    messages,
+                    message_view,
+                    cleared_messages,
+                    ..
+                } = &mut tab.state;
+                *cleared_messages = Some(ClearedMessages::take(messages, Instant::now()));
+                message_view.invalidate();
+            }
+        }
+        (PaletteAction::RecordMessages, Some(id)) => {
+            if !app.recorders.contains_key(&id) {
+                app.start_recording_from_file_picker(id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PaletteAction, fuzzy_score, matching_actions};
+
+    #[test]
+    fn fuzzy_matching_prefers_word_starts_and_runs() {
+        assert_eq!(fuzzy_score("xyz", "New tab"), None);
+        assert!(fuzzy_score("nt", "New tab").unwrap() > fuzzy_score("nt", "Reconnect").unwrap());
+        assert!(
+            fuzzy_score("disc", "Disconnect").unwrap() > fuzzy_score("dsc", "Disconnect").unwrap()
+        );
+
+        assert_eq!(matching_actions("about"), [PaletteAction::About]);
+        assert_eq!(matching_actions("").len(), PaletteAction::ALL.len());
+        assert_eq!(
+            matching_actions("disc all")[0],
+            PaletteAction::DisconnectAll
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use eframe::egui;
+
+use crate::models::protobuf::{ProtobufMapping, ProtobufSchema};
+use crate::utils::topic::validate_topic;
+
+pub(crate) struct ProtobufMenu<'a> {
+    pub(crate) id: u64,
+    pub(crate) schema: &'a mut Option<ProtobufSchema>,
+    pub(crate) mappings: &'a mut Vec<ProtobufMapping>,
+    pub(crate) topic_input: &'a mut String,
+    pub(crate) type_input: &'a mut String,
+    pub(crate) error: &'a mut Option<String>,
+}
+
+/// Loads a descriptor set and maps topic filters to the message types their payloads hold.
+/// Returns true when a schema was (re)loaded.
+pub(crate) fn render_protobuf_menu(ui: &mut egui::Ui, menu: ProtobufMenu<'_>) -> bool {
+    let ProtobufMenu {
+        id,
+        schema,
+        mappings,
+        topic_input,
+        type_input,
+        error,
+    } = menu;
+
+    let mut loaded_now = false;
+    ui.horizontal(|ui| {
+        match schema {
+            Some(loaded) => ui.label(format!(
+                "Schema: {}",
+                loaded
+                    .path
+                    .file_name()
+                    .map(|name| name.to_string_lossy())
+                    .unwrap_or_default()
+            )),
+            None => ui.label("No schema loaded"),
+        };
+        if ui
+            .button("Load descriptor set...")
+            .on_hover_text(
+                "A FileDescriptorSet, e.g. from protoc --include_imports --descriptor_set_out",
+            )
+            .clicked()
+            && let Some(path) = rfd::FileDialog::new()
+                .add_filter("Descriptor set", &["desc", "pb", "binpb"])
+                .pick_file()
+        {
+            match ProtobufSchema::load(&path) {
+                Ok(loaded) => {
+                    *schema = Some(loaded);
+                    *error = None;
+                    loaded_now = true;
+                }
+                Err(err) => *error = Some(err),
+            }
+        }
+    });
+    if let Some(err) = error {
+        ui.colored_label(ui.visuals().error_fg_color, err.as_str());
+    }
+
+    let Some(loaded) = schema else {
+        return loaded_now;
+    };
+
+    ui.separator();
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::TextEdit::singleline(topic_input)
+                .hint_text("sensors/+/reading")
+                .desired_width(140.0),
+        );
+        egui::ComboBox::from_id_salt(("protobuf_type", id))
+            .selected_text(if type_input.is_empty() {
+                "Message type"
+            } else {
+                type_input.as_str()
+            })
+            .show_ui(ui, |ui| {
+                for name in loaded.message_names() {
+                    let selected = *type_input == name;
+                    if ui.selectable_label(selected, &name).clicked() {
+                        *type_input = name;
+                    }
+                }
+            });
+        if ui.button("Add").clicked() {
+            let topic_filter = topic_input.trim().to_string();
+            if let Err(err) = validate_topic(&topic_filter, true) {
+                *error = Some(format!("Invalid topic filter: {err}"));
+            } else if !loaded.has_message(type_input) {
+                *error = Some("Pick a message type from the schema".to_string());
+            } else {
+                mappings.push(ProtobufMapping {
+                    topic_filter,
+                    message_type: type_input.clone(),
+                });
+                topic_input.clear();
+                *error = None;
+            }
+        }
+    });
+
+    if mappings.is_empty() {
+        ui.label("No topics mapped");
+    }
+    let mut remove = None;
+    for (index, mapping) in mappings.iter().enumerate() {
+        ui.horizontal(|ui| {
+            ui.monospace(&mapping.topic_filter);
+            ui.label("→");
+            let label = ui.monospace(&mapping.message_type);
+            if !loaded.has_message(&mapping.message_type) {
+                label.on_hover_text("Not in the loaded schema");
+            }
+            if ui.small_button("Remove").clicked() {
+                remove = Some(index);
+            }
+        });
+    }
+    if let Some(index) = remove {
+        mappings.remove(index);
+    }
+    loaded_now
+}
@@ -0,0 +1,230 @@
+use eframe::egui;
+
+use crate::app::App;
+use crate::app::settings::{
+    DEFAULT_CLIENT_SOFT_LIMIT, DEFAULT_COMMAND_QUEUE_LIMIT, DEFAULT_TOPIC_TREE_MAX_DEPTH,
+    DEFAULT_WORKER_THREADS, MAX_UI_SCALE, MAX_WORKER_THREADS, MIN_UI_SCALE,
+};
+use crate::ui::widgets::qos_picker;
+
+pub(crate) fn render_settings_window(app: &mut App, ctx: &egui::Context) {
+    if !app.show_settings {
+        return;
+    }
+
+    let mut open = app.show_settings;
+    let mut persist = false;
+
+    egui::Window::new("Settings")
+        .collapsible(false)
+        .resizable(false)
+        .open(&mut open)
+        .show(ctx, |ui| {
+            if let Some(status) = &app.settings_status {
+                ui.colored_label(ui.visuals().warn_fg_color, status);
+            }
+
+            ui.horizontal(|ui| {
+                ui.label("UI scale");
+                let response = ui.add(
+                    egui::Slider::new(&mut app.settings.ui_scale, MIN_UI_SCALE..=MAX_UI_SCALE)
+                        .step_by(0.05)
+                        .fixed_decimals(2),
+                );
+                if response.changed() {
+                    ctx.set_zoom_factor(app.settings.effective_ui_scale());
+                }
+                if response.drag_stopped() || (response.changed() && !response.dragged()) {
+                    persist = true;
+                }
+                if ui.button("Reset").clicked() {
+                    app.settings.ui_scale = 1.0;
+                    ctx.set_zoom_factor(1.0);
+                    persist = true;
+                }
+            });
+
+            ui.separator();
+            persist |= ui
+                .checkbox(
+                    &mut app.settings.restore_session,
+                    "Restore open tabs on startup",
+                )
+                .changed();
+            ui.add_enabled_ui(app.settings.restore_session, |ui| {
+                persist |= ui
+                    .checkbox(
+                        &mut app.settings.auto_connect_restored_tabs,
+                        "Connect restored tabs automatically",
+                    )
+                    .changed();
+            });
+            persist |= ui
+                .checkbox(&mut app.settings.tab_sidebar, "Show tabs in a sidebar")
+                .changed();
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                let mut limited = app.settings.command_queue_limit.is_some();
+                if ui
+                    .checkbox(&mut limited, "Limit queued commands per client")
+                    .on_hover_text("Commands sent while the queue is full are dropped and counted")
+                    .changed()
+                {
+                    app.settings.command_queue_limit =
+                        limited.then_some(DEFAULT_COMMAND_QUEUE_LIMIT);
+                    persist = true;
+                }
+                if let Some(limit) = &mut app.settings.command_queue_limit {
+                    let response = ui.add(egui::DragValue::new(limit).range(1..=100_000));
+                    persist |=
+                        response.drag_stopped() || (response.changed() && !response.dragged());
+                }
+            });
+            ui.label(
+                egui::RichText::new("Applies to clients connected after the change")
+                    .small()
+                    .weak(),
+            );
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                let mut limited = app.settings.client_soft_limit.is_some();
+                if ui
+                    .checkbox(&mut limited, "Warn above simultaneous clients")
+                    .on_hover_text("Clients still connect, but each new one past the limit warns")
+                    .changed()
+                {
+                    app.settings.client_soft_limit = limited.then_some(DEFAULT_CLIENT_SOFT_LIMIT);
+                    persist = true;
+                }
+                if let Some(limit) = &mut app.settings.client_soft_limit {
+                    let response = ui.add(egui::DragValue::new(limit).range(1..=1000));
+                    persist |=
+                        response.drag_stopped() || (response.changed() && !response.dragged());
+                }
+            });
+            ui.label(
+                egui::RichText::new(format!("{} clients running", app.running_clients()))
+                    .small()
+                    .weak(),
+            );
+
+            ui.horizontal(|ui| {
+                let mut limited = app.settings.worker_threads.is_some();
+                if ui
+                    .checkbox(&mut limited, "Limit runtime worker threads")
+                    .on_hover_text("Unlimited uses one worker per CPU core")
+                    .changed()
+                {
+                    app.settings.worker_threads = limited.then_some(DEFAULT_WORKER_THREADS);
+                    persist = true;
+                }
+                if let Some(threads) = &mut app.settings.worker_threads {
+                    let response =
+                        ui.add(egui::DragValue::new(threads).range(1..=MAX_WORKER_THREADS));
+                    persist |=
+                        response.drag_stopped() || (response.changed() && !response.dragged());
+                }
+            });
+            ui.label(
+                egui::RichText::new("Worker threads apply after restarting MQUI")
+                    .small()
+                    .weak(),
+            );
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                let mut limited = app.settings.topic_tree_max_depth.is_some();
+                if ui
+                    .checkbox(&mut limited, "Limit topic tree depth")
+                    .on_hover_text("Deeper topic levels are shown as one leaf")
+                    .changed()
+                {
+                    app.settings.topic_tree_max_depth =
+                        limited.then_some(DEFAULT_TOPIC_TREE_MAX_DEPTH);
+                    persist = true;
+                }
+                if let Some(depth) = &mut app.settings.topic_tree_max_depth {
+                    let response = ui.add(egui::DragValue::new(depth).range(1..=64));
+                    persist |=
+                        response.drag_stopped() || (response.changed() && !response.dragged());
+                }
+            });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Quick subscribe topic");
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut app.settings.quick_subscribe_topic)
+                        .hint_text("devices/+/status"),
+                );
+                persist |= response.lost_focus();
+            });
+            ui.label(
+                egui::RichText::new("Shown next to # and $SYS/# in the Subscriptions section")
+                    .small()
+                    .weak(),
+            );
+
+            ui.separator();
+            ui.label("New tab defaults");
+            egui::Grid::new("new_tab_defaults").show(ui, |ui| {
+                for (label, value) in [
+                    ("Subscribe topic", &mut app.settings.new_tab_subscribe_topic),
+                    ("Publish topic", &mut app.settings.new_tab_publish_topic),
+                    ("Publish payload", &mut app.settings.new_tab_publish_payload),
+                ] {
+                    ui.label(label);
+                    persist |= ui.text_edit_singleline(value).lost_focus();
+                    ui.end_row();
+                }
+                for (label, id, value) in [
+                    (
+                        "Subscribe QoS",
+                        "new_tab_subscribe_qos",
+                        &mut app.settings.new_tab_subscribe_qos,
+                    ),
+                    (
+                        "Publish QoS",
+                        "new_tab_publish_qos",
+                        &mut app.settings.new_tab_publish_qos,
+                    ),
+                ] {
+                    ui.label(label);
+                    let before = *value;
+                    qos_picker(ui, id, value, 2);
+                    persist |= *value != before;
+                    ui.end_row();
+                }
+            });
+            ui.label(
+                egui::RichText::new("Pre-filled in newly opened tabs; leave empty for none")
+                    .small()
+                    .weak(),
+            );
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Broker timestamp property");
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut app.settings.broker_timestamp_property)
+                        .hint_text("timestamp"),
+                );
+                persist |= response.lost_focus();
+            });
+            ui.label(
+                egui::RichText::new(
+                    "MQTT v5 user property holding a Unix timestamp (seconds or milliseconds)",
+                )
+                .small()
+                .weak(),
+            );
+        });
+
+    if persist {
+        app.persist_settings();
+    }
+
+    app.show_settings = open;
+}
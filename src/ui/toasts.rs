@@ -0,0 +1,57 @@
+use std::time::Instant;
+
+use eframe::egui;
+
+use crate::app::App;
+
+const TOAST_WIDTH: f32 = 280.0;
+
+/// Stacks error toasts in the bottom-right corner. Clicking "Show" switches to the tab the
+/// error came from.
+pub(crate) fn render_toasts(app: &mut App, ctx: &egui::Context) {
+    let now = Instant::now();
+    let toasts = app.toasts.visible(now);
+    let Some(next_expiry) = toasts.iter().filter_map(|toast| toast.remaining(now)).min() else {
+        return;
+    };
+
+    let mut dismiss = None;
+    let mut show_tab = None;
+    egui::Area::new(egui::Id::new("error_toasts"))
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-16.0, -16.0))
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            for toast in toasts {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.set_width(TOAST_WIDTH);
+                    ui.horizontal(|ui| {
+                        ui.label(
+                            egui::RichText::new(&toast.title)
+                                .color(ui.visuals().error_fg_color)
+                                .strong(),
+                        );
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui.small_button("x").on_hover_text("Dismiss").clicked() {
+                                dismiss = Some(toast.id);
+                            }
+                            if ui.small_button("Show").clicked() {
+                                show_tab = Some(toast.tab_id);
+                                dismiss = Some(toast.id);
+                            }
+                        });
+                    });
+                    ui.label(&toast.text);
+                });
+            }
+        });
+
+    if let Some(id) = dismiss {
+        app.toasts.dismiss(id);
+    }
+    if let Some(tab_id) = show_tab
+        && app.tabs.iter().any(|tab| tab.id == tab_id)
+    {
+        app.active_tab = Some(tab_id);
+    }
+    ctx.request_repaint_after(next_expiry);
+}
@@ -0,0 +1,65 @@
+use eframe::egui;
+
+use crate::models::latest::{LatestByTopic, TopicNode};
+use crate::models::mqtt::ReceivedMessage;
+use crate::ui::{topic_color_for, topic_label};
+use crate::utils::formatting::{PayloadEncoding, format_payload};
+
+const MAX_PREVIEW_CHARS: usize = 80;
+
+pub(crate) struct TopicTree<'a> {
+    pub(crate) id: u64,
+    pub(crate) latest: &'a LatestByTopic,
+    pub(crate) max_depth: Option<usize>,
+    pub(crate) encoding: PayloadEncoding,
+}
+
+/// Topics received so far as a tree of levels with each topic's latest payload. A branch's
+/// children are only looked up while it is expanded.
+pub(crate) fn render_topic_tree(ui: &mut egui::Ui, tree: &TopicTree<'_>) {
+    let top = tree.latest.children(None, tree.max_depth);
+    if top.is_empty() {
+        ui.label("No topics received yet.");
+        return;
+    }
+    render_nodes(ui, tree, top);
+}
+
+fn render_nodes(ui: &mut egui::Ui, tree: &TopicTree<'_>, nodes: Vec<TopicNode<'_>>) {
+    for node in nodes {
+        if node.descendants == 0 {
+            ui.horizontal(|ui| node_row(ui, tree, &node));
+            continue;
+        }
+
+        egui::CollapsingHeader::new(format!("{} ({})", node.label, node.descendants))
+            .id_salt(("topic_tree", tree.id, &node.path))
+            .show(ui, |ui| {
+                if node.message.is_some() {
+                    ui.horizontal(|ui| node_row(ui, tree, &node));
+                }
+                render_nodes(
+                    ui,
+                    tree,
+                    tree.latest.children(Some(&node.path), tree.max_depth),
+                );
+            });
+    }
+}
+
+fn node_row(ui: &mut egui::Ui, tree: &TopicTree<'_>, node: &TopicNode<'_>) {
+    let color = topic_color_for(&node.path, ui.visuals());
+    topic_label(ui, &node.label, color).on_hover_text(&node.path);
+    if let Some(message) = node.message {
+        ui.label(egui::RichText::new(preview(message, tree.encoding)).monospace());
+    }
+}
+
+fn preview(message: &ReceivedMessage, encoding: PayloadEncoding) -> String {
+    let mut text = format_payload(&message.payload, encoding);
+    if let Some((cut, _)) = text.char_indices().nth(MAX_PREVIEW_CHARS) {
+        text.truncate(cut);
+        text.push('…');
+    }
+    text.replace('\n', " ")
+}
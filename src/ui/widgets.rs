@@ -1,11 +1,141 @@
 use eframe::egui;
 
-pub(crate) fn qos_picker(ui: &mut egui::Ui, id: &str, value: &mut u8) {
+/// Picks a QoS up to the broker maximum. A higher value chosen earlier is lowered, since an
+/// MQTT 5 broker disconnects a client that publishes above its Maximum QoS (0x9B).
+pub(crate) fn qos_picker(ui: &mut egui::Ui, id: &str, value: &mut u8, maximum: u8) {
+    *value = (*value).min(maximum);
     egui::ComboBox::from_id_salt(id)
         .selected_text(value.to_string())
         .show_ui(ui, |ui| {
-            ui.selectable_value(value, 0, "0");
-            ui.selectable_value(value, 1, "1");
-            ui.selectable_value(value, 2, "2");
+            for level in 0..=2u8 {
+                ui.add_enabled_ui(level <= maximum, |ui| {
+                    ui.selectable_value(value, level, level.to_string())
+                        .on_disabled_hover_text(format!("Broker maximum QoS is {maximum}"));
+                });
+            }
+        });
+}
+
+pub(crate) const COLOR_TAG_PRESETS: [(&str, [u8; 3]); 6] = [
+    ("Red", [0xd0, 0x30, 0x30]),
+    ("Orange", [0xe0, 0x80, 0x20]),
+    ("Yellow", [0xd8, 0xc0, 0x30]),
+    ("Green", [0x30, 0xa0, 0x50]),
+    ("Blue", [0x30, 0x70, 0xd0]),
+    ("Purple", [0x90, 0x50, 0xc0]),
+];
+
+pub(crate) fn color_tag_name(value: Option<[u8; 3]>) -> &'static str {
+    match value {
+        None => "None",
+        Some(rgb) => COLOR_TAG_PRESETS
+            .iter()
+            .find(|(_, preset)| *preset == rgb)
+            .map(|(name, _)| *name)
+            .unwrap_or("Custom"),
+    }
+}
+
+pub(crate) fn color_swatch(ui: &mut egui::Ui, rgb: [u8; 3]) {
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
+    ui.painter()
+        .rect_filled(rect, 2.0, egui::Color32::from_rgb(rgb[0], rgb[1], rgb[2]));
+}
+
+pub(crate) fn color_tag_menu(ui: &mut egui::Ui) -> Option<Option<[u8; 3]>> {
+    let mut picked = None;
+    for (name, rgb) in COLOR_TAG_PRESETS {
+        ui.horizontal(|ui| {
+            color_swatch(ui, rgb);
+            if ui.button(name).clicked() {
+                picked = Some(Some(rgb));
+            }
+        });
+    }
+    ui.separator();
+    if ui.button("No color").clicked() {
+        picked = Some(None);
+    }
+    picked
+}
+
+pub(crate) fn color_tag_picker(ui: &mut egui::Ui, id: &str, value: &mut Option<[u8; 3]>) {
+    egui::ComboBox::from_id_salt(id)
+        .selected_text(color_tag_name(*value))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(value, None, "None");
+            for (name, rgb) in COLOR_TAG_PRESETS {
+                ui.horizontal(|ui| {
+                    color_swatch(ui, rgb);
+                    ui.selectable_value(value, Some(rgb), name);
+                });
+            }
+        });
+}
+
+pub(crate) fn contrast_text_color(background: egui::Color32) -> egui::Color32 {
+    let luminance = 0.299 * f32::from(background.r())
+        + 0.587 * f32::from(background.g())
+        + 0.114 * f32::from(background.b());
+    if luminance > 150.0 {
+        egui::Color32::BLACK
+    } else {
+        egui::Color32::WHITE
+    }
+}
+
+pub(crate) fn code_editor(ui: &mut egui::Ui, id: impl std::hash::Hash, text: &mut String) {
+    let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+    let digit_width = ui.fonts_mut(|fonts| fonts.glyph_width(&font_id, '0'));
+
+    egui::Resize::default()
+        .id_salt(("code_editor_resize", &id))
+        .default_height(140.0)
+        .min_height(60.0)
+        .max_width(ui.available_width())
+        .show(ui, |ui| {
+            egui::ScrollArea::both()
+                .id_salt(("code_editor_scroll", &id))
+                .auto_shrink([false, false])
+                .show(ui, |ui| {
+                    ui.horizontal_top(|ui| {
+                        let line_count = text.split('\n').count();
+                        let digits = line_count.to_string().len().max(2);
+                        let mut numbers = (1..=line_count)
+                            .map(|line| format!("{line:>digits$}"))
+                            .collect::<Vec<_>>()
+                            .join("\n");
+
+                        ui.add(
+                            egui::TextEdit::multiline(&mut numbers)
+                                .code_editor()
+                                .interactive(false)
+                                .frame(false)
+                                .desired_width(digit_width * digits as f32),
+                        );
+                        // Lines never wrap, so each one stays level with its gutter number.
+                        let mut layouter =
+                            |ui: &egui::Ui, buffer: &dyn egui::TextBuffer, _wrap_width: f32| {
+                                let color = ui
+                                    .visuals()
+                                    .override_text_color
+                                    .unwrap_or_else(|| ui.visuals().widgets.inactive.text_color());
+                                let job = egui::text::LayoutJob::simple(
+                                    buffer.as_str().to_owned(),
+                                    font_id.clone(),
+                                    color,
+                                    f32::INFINITY,
+                                );
+                                ui.fonts_mut(|fonts| fonts.layout_job(job))
+                            };
+                        ui.add(
+                            egui::TextEdit::multiline(text)
+                                .code_editor()
+                                .layouter(&mut layouter)
+                                .desired_rows(6)
+                                .desired_width(f32::INFINITY),
+                        );
+                    });
+                });
         });
 }
@@ -1,4 +1,9 @@
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
 
 pub(crate) fn format_timestamp(ts: SystemTime) -> String {
     match ts.duration_since(UNIX_EPOCH) {
@@ -7,21 +12,382 @@ pub(crate) fn format_timestamp(ts: SystemTime) -> String {
     }
 }
 
-pub(crate) fn format_payload(payload: &[u8], as_hex: bool) -> String {
-    if as_hex {
-        return payload
-            .iter()
-            .map(|byte| format!("{byte:02X}"))
-            .collect::<Vec<_>>()
-            .join(" ");
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum PayloadEncoding {
+    #[default]
+    Utf8,
+    Lossy,
+    Hex,
+}
+
+impl PayloadEncoding {
+    pub(crate) const ALL: [Self; 3] = [Self::Utf8, Self::Lossy, Self::Hex];
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::Utf8 => "UTF-8",
+            Self::Lossy => "Lossy text",
+            Self::Hex => "Hex",
+        }
+    }
+}
+
+/// How structured payloads are decoded for display. `Auto` pretty-prints JSON when the
+/// content type says so and tries CBOR, then MessagePack, for payloads that are not UTF-8.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum PayloadDecoder {
+    #[default]
+    Auto,
+    Json,
+    Cbor,
+    MessagePack,
+    Raw,
+}
+
+impl PayloadDecoder {
+    pub(crate) const ALL: [Self; 5] = [
+        Self::Auto,
+        Self::Json,
+        Self::Cbor,
+        Self::MessagePack,
+        Self::Raw,
+    ];
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::Auto => "Auto",
+            Self::Json => "JSON",
+            Self::Cbor => "CBOR",
+            Self::MessagePack => "MessagePack",
+            Self::Raw => "Raw",
+        }
+    }
+
+    fn for_mime(mime: &str) -> Option<Self> {
+        match mime {
+            "application/json" => Some(Self::Json),
+            "application/cbor" => Some(Self::Cbor),
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => {
+                Some(Self::MessagePack)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Decodes a payload into pretty-printed JSON-like text, or `None` when the decoder does not
+/// apply and the payload should be shown with [`format_payload`]. `mime` is the lowercased
+/// content type without parameters.
+pub(crate) fn decode_payload(
+    payload: &[u8],
+    decoder: PayloadDecoder,
+    mime: Option<&str>,
+) -> Option<String> {
+    match decoder {
+        PayloadDecoder::Raw => None,
+        PayloadDecoder::Json => format_json_payload(payload),
+        PayloadDecoder::Cbor => format_cbor_payload(payload),
+        PayloadDecoder::MessagePack => format_msgpack_payload(payload),
+        PayloadDecoder::Auto => match mime.and_then(PayloadDecoder::for_mime) {
+            Some(decoder) => decode_payload(payload, decoder, None),
+            None if std::str::from_utf8(payload).is_err() => {
+                format_cbor_payload(payload).or_else(|| format_msgpack_payload(payload))
+            }
+            None => None,
+        },
+    }
+}
+
+fn format_cbor_payload(mut payload: &[u8]) -> Option<String> {
+    let value: ciborium::Value = ciborium::from_reader(&mut payload).ok()?;
+    payload.is_empty().then_some(())?;
+    serde_json::to_string_pretty(&value).ok()
+}
+
+fn format_msgpack_payload(mut payload: &[u8]) -> Option<String> {
+    // Decoded through CBOR's value type, which unlike serde_json's keeps binary strings and
+    // non-string map keys.
+    let value: ciborium::Value = rmp_serde::from_read(&mut payload).ok()?;
+    payload.is_empty().then_some(())?;
+    serde_json::to_string_pretty(&value).ok()
+}
+
+/// Parses a Unix timestamp in seconds or milliseconds, telling them apart by magnitude.
+pub(crate) fn parse_epoch_timestamp(value: &str) -> Option<SystemTime> {
+    let value = value.trim().parse::<f64>().ok()?;
+    if !value.is_finite() || value < 0.0 {
+        return None;
+    }
+
+    let secs = if value >= 1e11 { value / 1000.0 } else { value };
+    UNIX_EPOCH.checked_add(Duration::try_from_secs_f64(secs).ok()?)
+}
+
+/// Seconds since the epoch that a bare numeric payload is assumed to be a timestamp in:
+/// 2001-09-09 up to 2100-01-01.
+const PLAUSIBLE_EPOCH_SECS: std::ops::Range<f64> = 1e9..4_102_444_800.0;
+
+/// Reads a payload that is just a number. Values in the plausible Unix timestamp range, in
+/// seconds or milliseconds, become a UTC date; other decimals are shown normalised. Plain
+/// integers and anything that is not a number yield `None`.
+pub(crate) fn interpret_number(payload: &[u8]) -> Option<String> {
+    let text = std::str::from_utf8(payload).ok()?.trim();
+    if text.is_empty() || !text.starts_with(|c: char| c == '-' || c.is_ascii_digit()) {
+        return None;
+    }
+    let value = text.parse::<f64>().ok().filter(|value| value.is_finite())?;
+
+    if PLAUSIBLE_EPOCH_SECS.contains(&value) {
+        let date = format_utc(parse_epoch_timestamp(text)?);
+        return Some(format!("{date} (Unix seconds)"));
+    }
+    if PLAUSIBLE_EPOCH_SECS.contains(&(value / 1000.0)) {
+        let date = format_utc(parse_epoch_timestamp(text)?);
+        return Some(format!("{date} (Unix milliseconds)"));
+    }
+    if text.parse::<i64>().is_ok() {
+        return None;
+    }
+    let number = value.to_string();
+    (number != text).then(|| format!("number {number}"))
+}
+
+/// Formats a time as `YYYY-MM-DD HH:MM:SS.mmm UTC`.
+pub(crate) fn format_utc(ts: SystemTime) -> String {
+    let since_epoch = ts.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+
+    // Civil date from days since 1970-01-01, after Howard Hinnant's `civil_from_days`.
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}.{:03} UTC",
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+pub(crate) fn format_payload(payload: &[u8], encoding: PayloadEncoding) -> String {
+    match encoding {
+        PayloadEncoding::Utf8 => match std::str::from_utf8(payload) {
+            Ok(text) => text.to_string(),
+            Err(_) => format_hex(payload),
+        },
+        PayloadEncoding::Lossy => String::from_utf8_lossy(payload).into_owned(),
+        PayloadEncoding::Hex => format_hex(payload),
+    }
+}
+
+fn format_hex(payload: &[u8]) -> String {
+    payload
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub(crate) fn format_json_payload(payload: &[u8]) -> Option<String> {
+    let value = serde_json::from_slice::<serde_json::Value>(payload).ok()?;
+    serde_json::to_string_pretty(&value).ok()
+}
+
+pub(crate) fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+pub(crate) fn format_color_hex(rgb: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", rgb[0], rgb[1], rgb[2])
+}
+
+pub(crate) fn parse_color_hex(value: &str) -> Option<[u8; 3]> {
+    let hex = value.trim().strip_prefix('#')?;
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+
+    let channel = |range: std::ops::Range<usize>| u8::from_str_radix(&hex[range], 16).ok();
+    Some([channel(0..2)?, channel(2..4)?, channel(4..6)?])
+}
+
+pub(crate) fn encode_base64(input: &[u8]) -> String {
+    let mut output = String::with_capacity(input.len().div_ceil(3) * 4);
+    for chunk in input.chunks(3) {
+        let bytes = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let triple = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for index in 0..4 {
+            if index <= chunk.len() {
+                let sextet = (triple >> (18 - index * 6)) & 0x3f;
+                output.push(char::from(BASE64_ALPHABET[sextet as usize]));
+            } else {
+                output.push('=');
+            }
+        }
+    }
+    output
+}
+
+pub(crate) fn decode_base64(input: &str) -> Result<Vec<u8>, String> {
+    let input = input.trim_end_matches('=');
+    let mut output = Vec::with_capacity(input.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for ch in input.bytes() {
+        let Some(sextet) = BASE64_ALPHABET.iter().position(|&symbol| symbol == ch) else {
+            return Err(format!("invalid base64 character '{}'", char::from(ch)));
+        };
+        buffer = (buffer << 6) | sextet as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::{
+        PayloadDecoder, PayloadEncoding, decode_base64, decode_payload, encode_base64,
+        format_bytes, format_payload, format_utc, interpret_number, parse_epoch_timestamp,
+    };
+
+    #[test]
+    fn base64_matches_reference_vectors() {
+        assert_eq!(encode_base64(b""), "");
+        assert_eq!(encode_base64(b"f"), "Zg==");
+        assert_eq!(encode_base64(b"fo"), "Zm8=");
+        assert_eq!(encode_base64(b"foo"), "Zm9v");
+        assert_eq!(encode_base64(b"user:pass"), "dXNlcjpwYXNz");
+    }
+
+    #[test]
+    fn base64_round_trips() {
+        for input in [&b""[..], b"f", b"fo", b"foo", b"\x00\xff\x10binary"] {
+            assert_eq!(decode_base64(&encode_base64(input)).unwrap(), input);
+        }
+        assert!(decode_base64("not*base64").is_err());
+    }
+
+    #[test]
+    fn binary_payloads_decode_as_cbor_or_messagepack() {
+        // {"t": 21} in CBOR and MessagePack.
+        let cbor = [0xa1, 0x61, b't', 0x15];
+        let msgpack = [0x81, 0xa1, b't', 0x15];
+        let expected = "{\n  \"t\": 21\n}";
+
+        assert_eq!(
+            decode_payload(&cbor, PayloadDecoder::Cbor, None).as_deref(),
+            Some(expected)
+        );
+        assert_eq!(
+            decode_payload(&msgpack, PayloadDecoder::MessagePack, None).as_deref(),
+            Some(expected)
+        );
+        assert_eq!(
+            decode_payload(&cbor, PayloadDecoder::Auto, None).as_deref(),
+            Some(expected)
+        );
+        assert_eq!(
+            decode_payload(
+                &msgpack,
+                PayloadDecoder::Auto,
+                Some("application/x-msgpack")
+            )
+            .as_deref(),
+            Some(expected)
+        );
+        assert_eq!(decode_payload(&cbor, PayloadDecoder::Raw, None), None);
+        assert_eq!(decode_payload(b"5", PayloadDecoder::Auto, None), None);
+        assert_eq!(
+            decode_payload(&[0xa1, 0x61, b't', 0x15, 0x00], PayloadDecoder::Cbor, None),
+            None
+        );
+    }
+
+    #[test]
+    fn byte_counts_use_binary_units() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(5 * 1024 * 1024), "5.0 MiB");
+    }
+
+    #[test]
+    fn payload_encodings_handle_invalid_utf8() {
+        let payload = b"temp \xff 21";
+        assert_eq!(
+            format_payload(payload, PayloadEncoding::Utf8),
+            "74 65 6D 70 20 FF 20 32 31"
+        );
+        assert_eq!(
+            format_payload(payload, PayloadEncoding::Lossy),
+            "temp \u{FFFD} 21"
+        );
+        assert_eq!(format_payload(b"ok", PayloadEncoding::Utf8), "ok");
+        assert_eq!(format_payload(b"ok", PayloadEncoding::Hex), "6F 6B");
+    }
+
+    #[test]
+    fn epoch_timestamps_accept_seconds_and_milliseconds() {
+        let expected = UNIX_EPOCH + Duration::from_millis(1_700_000_000_250);
+        assert_eq!(parse_epoch_timestamp("1700000000.25"), Some(expected));
+        assert_eq!(parse_epoch_timestamp(" 1700000000250 "), Some(expected));
+        assert_eq!(parse_epoch_timestamp("-5"), None);
+        assert_eq!(parse_epoch_timestamp("yesterday"), None);
     }
 
-    match String::from_utf8(payload.to_vec()) {
-        Ok(text) => text,
-        Err(_) => payload
-            .iter()
-            .map(|byte| format!("{byte:02X}"))
-            .collect::<Vec<_>>()
-            .join(" "),
+    #[test]
+    fn bare_numbers_are_interpreted_as_dates_or_decimals() {
+        assert_eq!(
+            format_utc(UNIX_EPOCH + Duration::from_millis(1_700_000_000_250)),
+            "2023-11-14 22:13:20.250 UTC"
+        );
+        assert_eq!(format_utc(UNIX_EPOCH), "1970-01-01 00:00:00.000 UTC");
+        assert_eq!(
+            interpret_number(b"1700000000").as_deref(),
+            Some("2023-11-14 22:13:20.000 UTC (Unix seconds)")
+        );
+        assert_eq!(
+            interpret_number(b"1700000000250\n").as_deref(),
+            Some("2023-11-14 22:13:20.250 UTC (Unix milliseconds)")
+        );
+        assert_eq!(interpret_number(b"2.150e1").as_deref(), Some("number 21.5"));
+        assert_eq!(interpret_number(b"21.5"), None);
+        assert_eq!(interpret_number(b"42"), None);
+        assert_eq!(interpret_number(b"inf"), None);
+        assert_eq!(interpret_number(b"{\"a\":1}"), None);
     }
 }
@@ -0,0 +1,131 @@
+use serde_json::Value;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// Parses the JSONPath subset used by message columns: `$`, `.field`, `['field']` and
+/// `[index]`, e.g. `$.sensors[0].temperature`. The leading `$` is optional.
+pub(crate) fn parse_json_path(path: &str) -> Result<Vec<PathSegment>, String> {
+    let path = path.trim();
+    let mut rest = path.strip_prefix('$').unwrap_or(path);
+    let mut segments = Vec::new();
+
+    if !rest.is_empty() && !rest.starts_with(['.', '[']) {
+        let end = rest.find(['.', '[']).unwrap_or(rest.len());
+        segments.push(PathSegment::Key(rest[..end].to_string()));
+        rest = &rest[end..];
+    }
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            if end == 0 {
+                return Err(format!("Empty field name in '{path}'"));
+            }
+            segments.push(PathSegment::Key(after[..end].to_string()));
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after
+                .find(']')
+                .ok_or_else(|| format!("Unclosed '[' in '{path}'"))?;
+            let inner = after[..end].trim();
+            let quoted = inner
+                .strip_prefix('\'')
+                .and_then(|inner| inner.strip_suffix('\''))
+                .or_else(|| {
+                    inner
+                        .strip_prefix('"')
+                        .and_then(|inner| inner.strip_suffix('"'))
+                });
+            let segment = match quoted {
+                Some(key) => PathSegment::Key(key.to_string()),
+                None => PathSegment::Index(
+                    inner
+                        .parse()
+                        .map_err(|_| format!("'{inner}' is not an array index in '{path}'"))?,
+                ),
+            };
+            segments.push(segment);
+            rest = &after[end + 1..];
+        } else {
+            return Err(format!("Unexpected '{rest}' in '{path}'"));
+        }
+    }
+
+    if segments.is_empty() {
+        return Err("Path must select a field, e.g. $.temperature".to_string());
+    }
+    Ok(segments)
+}
+
+fn select<'a>(value: &'a Value, segments: &[PathSegment]) -> Option<&'a Value> {
+    segments
+        .iter()
+        .try_fold(value, |value, segment| match segment {
+            PathSegment::Key(key) => value.get(key),
+            PathSegment::Index(index) => value.get(index),
+        })
+}
+
+/// Evaluates each path against a JSON payload. Paths that do not match, and every path for a
+/// payload that is not JSON, yield an empty string.
+pub(crate) fn extract_columns(payload: &[u8], paths: &[Vec<PathSegment>]) -> Vec<String> {
+    let Ok(value) = serde_json::from_slice::<Value>(payload) else {
+        return vec![String::new(); paths.len()];
+    };
+    paths
+        .iter()
+        .map(|segments| {
+            select(&value, segments)
+                .map(format_value)
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Null => "null".to_string(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PathSegment, extract_columns, parse_json_path};
+
+    #[test]
+    fn parses_dot_and_bracket_paths() {
+        assert_eq!(
+            parse_json_path("$.sensors[0]['temp c']").unwrap(),
+            [
+                PathSegment::Key("sensors".to_string()),
+                PathSegment::Index(0),
+                PathSegment::Key("temp c".to_string()),
+            ]
+        );
+        assert_eq!(
+            parse_json_path("temperature").unwrap(),
+            [PathSegment::Key("temperature".to_string())]
+        );
+        assert!(parse_json_path("$").is_err());
+        assert!(parse_json_path("$.a..b").is_err());
+        assert!(parse_json_path("$.a[x]").is_err());
+        assert!(parse_json_path("$.a[0").is_err());
+    }
+
+    #[test]
+    fn extracts_columns_and_blanks_misses() {
+        let paths: Vec<_> = ["$.temperature", "$.meta.unit", "$.readings[1]", "$.missing"]
+            .into_iter()
+            .map(|path| parse_json_path(path).unwrap())
+            .collect();
+        let payload = br#"{"temperature":21.5,"meta":{"unit":"C"},"readings":[1,2]}"#;
+        assert_eq!(extract_columns(payload, &paths), ["21.5", "C", "2", ""]);
+        assert_eq!(extract_columns(b"not json", &paths), ["", "", "", ""]);
+    }
+}
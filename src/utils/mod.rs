@@ -1,2 +1,5 @@
 pub(crate) mod formatting;
+pub(crate) mod json_path;
 pub(crate) mod qos;
+pub(crate) mod reason;
+pub(crate) mod topic;
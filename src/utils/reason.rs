@@ -0,0 +1,110 @@
+/// Human-readable name for an MQTT 5 reason code. Codes at or above 0x80 share one meaning
+/// across packet types; the low codes only occur in the packets that define them.
+pub(crate) fn reason_name(code: u8) -> &'static str {
+    match code {
+        0x00 => "Success",
+        0x01 => "Granted QoS 1",
+        0x02 => "Granted QoS 2",
+        0x04 => "Disconnect With Will Message",
+        0x10 => "No Matching Subscribers",
+        0x11 => "No Subscription Existed",
+        0x18 => "Continue Authentication",
+        0x19 => "Re-authenticate",
+        0x80 => "Unspecified Error",
+        0x81 => "Malformed Packet",
+        0x82 => "Protocol Error",
+        0x83 => "Implementation Specific Error",
+        0x84 => "Unsupported Protocol Version",
+        0x85 => "Client Identifier Not Valid",
+        0x86 => "Bad User Name or Password",
+        0x87 => "Not Authorized",
+        0x88 => "Server Unavailable",
+        0x89 => "Server Busy",
+        0x8a => "Banned",
+        0x8b => "Server Shutting Down",
+        0x8c => "Bad Authentication Method",
+        0x8d => "Keep Alive Timeout",
+        0x8e => "Session Taken Over",
+        0x8f => "Topic Filter Invalid",
+        0x90 => "Topic Name Invalid",
+        0x91 => "Packet Identifier In Use",
+        0x92 => "Packet Identifier Not Found",
+        0x93 => "Receive Maximum Exceeded",
+        0x94 => "Topic Alias Invalid",
+        0x95 => "Packet Too Large",
+        0x96 => "Message Rate Too High",
+        0x97 => "Quota Exceeded",
+        0x98 => "Administrative Action",
+        0x99 => "Payload Format Invalid",
+        0x9a => "Retain Not Supported",
+        0x9b => "QoS Not Supported",
+        0x9c => "Use Another Server",
+        0x9d => "Server Moved",
+        0x9e => "Shared Subscriptions Not Supported",
+        0x9f => "Connection Rate Exceeded",
+        0xa0 => "Maximum Connect Time",
+        0xa1 => "Subscription Identifiers Not Supported",
+        0xa2 => "Wildcard Subscriptions Not Supported",
+        _ => "Unknown Reason Code",
+    }
+}
+
+/// Like [`reason_name`], but names 0x00 the way SUBACK means it.
+pub(crate) fn suback_reason_name(code: u8) -> &'static str {
+    match code {
+        0x00 => "Granted QoS 0",
+        _ => reason_name(code),
+    }
+}
+
+/// Like [`reason_name`], but names 0x00 the way DISCONNECT means it.
+pub(crate) fn disconnect_reason_name(code: u8) -> &'static str {
+    match code {
+        0x00 => "Normal Disconnection",
+        _ => reason_name(code),
+    }
+}
+
+/// Formats a reason code as `0x87 Not Authorized`.
+pub(crate) fn describe_reason(code: u8, name: fn(u8) -> &'static str) -> String {
+    format!("{code:#04x} {}", name(code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{describe_reason, disconnect_reason_name, reason_name, suback_reason_name};
+
+    #[test]
+    fn reason_codes_have_friendly_names() {
+        let table = [
+            (0x00, "0x00 Success"),
+            (0x10, "0x10 No Matching Subscribers"),
+            (0x11, "0x11 No Subscription Existed"),
+            (0x80, "0x80 Unspecified Error"),
+            (0x86, "0x86 Bad User Name or Password"),
+            (0x87, "0x87 Not Authorized"),
+            (0x88, "0x88 Server Unavailable"),
+            (0x8e, "0x8e Session Taken Over"),
+            (0x8f, "0x8f Topic Filter Invalid"),
+            (0x97, "0x97 Quota Exceeded"),
+            (0x9f, "0x9f Connection Rate Exceeded"),
+            (0xa2, "0xa2 Wildcard Subscriptions Not Supported"),
+            (0x7f, "0x7f Unknown Reason Code"),
+        ];
+        for (code, expected) in table {
+            assert_eq!(describe_reason(code, reason_name), expected);
+        }
+    }
+
+    #[test]
+    fn success_code_depends_on_packet_type() {
+        assert_eq!(
+            describe_reason(0x00, suback_reason_name),
+            "0x00 Granted QoS 0"
+        );
+        assert_eq!(suback_reason_name(0x02), "Granted QoS 2");
+        assert_eq!(suback_reason_name(0x87), "Not Authorized");
+        assert_eq!(disconnect_reason_name(0x00), "Normal Disconnection");
+        assert_eq!(disconnect_reason_name(0x04), "Disconnect With Will Message");
+    }
+}
@@ -0,0 +1,174 @@
+pub(crate) fn topic_matches(filter: &str, topic: &str) -> bool {
+    if topic.starts_with('$') && (filter.starts_with('+') || filter.starts_with('#')) {
+        return false;
+    }
+
+    let mut filter_levels = filter.split('/');
+    let mut topic_levels = topic.split('/');
+
+    loop {
+        match (filter_levels.next(), topic_levels.next()) {
+            (Some("#"), _) => return true,
+            (Some("+"), Some(_)) => {}
+            (Some(expected), Some(actual)) if expected == actual => {}
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+/// Filters that cover `topic`, from the exact topic to its widest wildcarded parent:
+/// `a/b/c` yields `a/b/c`, `a/b/+`, `a/b/#` and `a/#`.
+pub(crate) fn subscription_patterns(topic: &str) -> Vec<String> {
+    let levels: Vec<&str> = topic.split('/').collect();
+    let mut patterns = vec![topic.to_string()];
+
+    let parent = &levels[..levels.len() - 1];
+    if !parent.is_empty() {
+        patterns.push(format!("{}/+", parent.join("/")));
+    } else if !topic.starts_with('$') {
+        patterns.push("+".to_string());
+    }
+    for depth in (1..levels.len()).rev() {
+        patterns.push(format!("{}/#", levels[..depth].join("/")));
+    }
+
+    patterns
+}
+
+/// Splits newline-separated publish topics, skipping blank lines. Topic names must not be
+/// empty or contain wildcards.
+pub(crate) fn publish_topics(input: &str) -> Result<Vec<String>, String> {
+    let topics: Vec<String> = input
+        .lines()
+        .map(str::trim)
+        .filter(|topic| !topic.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    if topics.is_empty() {
+        return Err("Publish topic cannot be empty".to_string());
+    }
+    for topic in &topics {
+        validate_topic(topic, false)
+            .map_err(|err| format!("Invalid publish topic '{topic}': {err}"))?;
+    }
+    Ok(topics)
+}
+
+/// Checks a topic name, or a topic filter when `wildcards` is set, before it is sent so that
+/// mistakes are reported next to the field rather than as a failed packet.
+pub(crate) fn validate_topic(topic: &str, wildcards: bool) -> Result<(), String> {
+    if topic.is_empty() {
+        return Err("topic cannot be empty".to_string());
+    }
+    if topic.len() > usize::from(u16::MAX) {
+        return Err(format!(
+            "topic is {} bytes, the limit is {}",
+            topic.len(),
+            u16::MAX
+        ));
+    }
+    if topic.contains('\0') {
+        return Err("null characters are not allowed".to_string());
+    }
+    if let Some(ch) = topic.chars().find(|ch| ch.is_control()) {
+        return Err(format!(
+            "control character U+{:04X} is not allowed",
+            u32::from(ch)
+        ));
+    }
+    if !wildcards {
+        if topic.contains(['+', '#']) {
+            return Err("wildcards are not allowed".to_string());
+        }
+        return Ok(());
+    }
+
+    let levels: Vec<&str> = topic.split('/').collect();
+    for (index, level) in levels.iter().enumerate() {
+        if level.contains('+') && *level != "+" {
+            return Err("'+' must fill a whole topic level".to_string());
+        }
+        if level.contains('#') && (*level != "#" || index + 1 != levels.len()) {
+            return Err("'#' must be the last topic level on its own".to_string());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{publish_topics, subscription_patterns, topic_matches, validate_topic};
+
+    #[test]
+    fn exact_and_wildcard_filters_match() {
+        assert!(topic_matches("a/b", "a/b"));
+        assert!(!topic_matches("a/b", "a/c"));
+        assert!(topic_matches("a/+/c", "a/b/c"));
+        assert!(!topic_matches("a/+", "a/b/c"));
+        assert!(topic_matches("a/#", "a"));
+        assert!(topic_matches("a/#", "a/b/c"));
+        assert!(topic_matches("#", "a/b"));
+        assert!(topic_matches("+/+", "/b"));
+    }
+
+    #[test]
+    fn wildcards_do_not_match_system_topics() {
+        assert!(!topic_matches("#", "$SYS/broker/uptime"));
+        assert!(!topic_matches("+/broker/uptime", "$SYS/broker/uptime"));
+        assert!(topic_matches("$SYS/#", "$SYS/broker/uptime"));
+    }
+
+    #[test]
+    fn subscription_patterns_widen_towards_the_root() {
+        assert_eq!(
+            subscription_patterns("sensors/room1/temp"),
+            [
+                "sensors/room1/temp",
+                "sensors/room1/+",
+                "sensors/room1/#",
+                "sensors/#"
+            ]
+        );
+        assert_eq!(subscription_patterns("status"), ["status", "+"]);
+        assert_eq!(subscription_patterns("$SYS"), ["$SYS"]);
+        for pattern in subscription_patterns("a//c") {
+            assert!(topic_matches(&pattern, "a//c"), "{pattern}");
+        }
+    }
+
+    #[test]
+    fn publish_topics_skip_blank_lines_and_reject_wildcards() {
+        assert_eq!(publish_topics("a/b\n\n  c/d  \n").unwrap(), ["a/b", "c/d"]);
+        assert_eq!(
+            publish_topics(" \n").unwrap_err(),
+            "Publish topic cannot be empty"
+        );
+        assert_eq!(
+            publish_topics("a/b\na/+").unwrap_err(),
+            "Invalid publish topic 'a/+': wildcards are not allowed"
+        );
+    }
+
+    #[test]
+    fn topics_are_validated_before_sending() {
+        assert_eq!(validate_topic("a/b", false), Ok(()));
+        assert_eq!(validate_topic("a/+/c/#", true), Ok(()));
+        assert_eq!(
+            validate_topic("a/\0b", false).unwrap_err(),
+            "null characters are not allowed"
+        );
+        assert_eq!(
+            validate_topic("a/\u{1b}[0m", true).unwrap_err(),
+            "control character U+001B is not allowed"
+        );
+        assert_eq!(
+            validate_topic("a/#", false).unwrap_err(),
+            "wildcards are not allowed"
+        );
+        assert!(validate_topic("a/b+", true).is_err());
+        assert!(validate_topic("a/#/c", true).is_err());
+        assert!(validate_topic("", true).is_err());
+    }
+}